@@ -0,0 +1,392 @@
+// service bulletins and detours from BusTime (getservicebulletins, getdetours), for the routes
+// serving the configured stops, each asked of the feed that serves them (see Stops::routes),
+// so a route detoured away from a stop still gets its alert
+// they are attached to the route groups they concern and listed in full at /alerts
+//
+// they change rarely, so a background task refreshes them every ALERT_REFRESH_SECONDS rather
+// than on every poll, and not at all while polling is suspended
+// the same bulletin can come back for several batches of routes or several feeds, so alerts
+// are merged by id; ones PRT stopped returning are dropped after a refresh that fully
+// succeeded, and otherwise once they haven't been seen for ALERT_EXPIRY_SECONDS
+
+use crate::config::{Config, FeedConfig};
+use crate::poller::Poller;
+use crate::stops::Stops;
+use crate::upstream::Upstream;
+use crate::{AppError, AppState, FrontendResponse, bustime};
+use axum::{Json, extract::State};
+use chrono::{DateTime, FixedOffset, TimeDelta, Utc};
+use futures_util::{FutureExt, future::join_all};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::sleep;
+
+// time between refreshes
+const ALERT_REFRESH_SECONDS: u64 = 10 * 60;
+
+// an alert missing from this long (because refreshes failed) is no longer shown
+const ALERT_EXPIRY_SECONDS: i64 = 60 * 60;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertKind {
+    Bulletin,
+    Detour,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Alert {
+    // "bulletin:<name>" or "detour:<id>", stable across refreshes
+    pub id: String,
+    pub kind: AlertKind,
+    pub title: String,
+    // plain text, may be empty
+    pub detail: String,
+    // where it applies, sorted, never empty
+    pub applies_to: Vec<AlertScope>,
+    pub starts_at: Option<DateTime<FixedOffset>>,
+    pub ends_at: Option<DateTime<FixedOffset>>,
+}
+
+// a route at a stop, with None for "any": a route at every stop, every route at a stop, or
+// (both None) everywhere
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AlertScope {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub route: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<String>,
+}
+
+impl AlertScope {
+    // sorted and once each, or everywhere if there are none
+    pub fn list(scopes: impl IntoIterator<Item = AlertScope>) -> Vec<AlertScope> {
+        let mut list: Vec<AlertScope> = scopes.into_iter().collect();
+        if list.is_empty() {
+            list.push(AlertScope::default());
+        }
+        list.sort();
+        list.dedup();
+        list
+    }
+
+    fn matches(&self, route: &str, stop: &str) -> bool {
+        self.route.as_ref().is_none_or(|r| r == route)
+            && self.stop.as_ref().is_none_or(|s| s == stop)
+    }
+}
+
+#[derive(Serialize)]
+pub struct AlertsResponse {
+    // None until the first refresh
+    fetched_at: Option<DateTime<Utc>>,
+    alerts: Vec<Alert>,
+}
+
+pub struct Alerts {
+    upstream: Arc<Upstream>,
+    feeds: Vec<FeedConfig>,
+    known: Mutex<Known>,
+}
+
+#[derive(Default)]
+struct Known {
+    fetched_at: Option<DateTime<Utc>>,
+    alerts: Vec<Seen>,
+}
+
+struct Seen {
+    alert: Alert,
+    seen_at: DateTime<Utc>,
+}
+
+impl Alerts {
+    pub fn spawn(
+        upstream: Arc<Upstream>,
+        feeds: &[FeedConfig],
+        config: Arc<Config>,
+        poller: Arc<Poller>,
+        stops: Arc<Stops>,
+    ) -> Arc<Self> {
+        let alerts = Arc::new(Alerts {
+            upstream,
+            feeds: bustime::feeds(feeds),
+            known: Mutex::new(Known::default()),
+        });
+
+        let background = alerts.clone();
+        tokio::spawn(async move {
+            // until the stops lookup is done, the predictions fill in the routes to ask about
+            let mut updates = poller.subscribe();
+            if updates.wait_for(Option::is_some).await.is_err() {
+                return;
+            }
+
+            loop {
+                if !config.polling_suspended(Utc::now()) {
+                    let data = poller
+                        .latest()
+                        .and_then(|poll| poll.snapshot.as_ref().map(|s| s.data.clone()))
+                        .unwrap_or_default();
+                    background
+                        .refresh(stops.routes(&background.feeds, &data))
+                        .await;
+                }
+                sleep(Duration::from_secs(ALERT_REFRESH_SECONDS)).await;
+            }
+        });
+
+        alerts
+    }
+
+    // asks PRT about `routes` (one list per feed)
+    async fn refresh(&self, routes: Vec<Vec<String>>) {
+        let calls = self
+            .feeds
+            .iter()
+            .zip(&routes)
+            .filter(|(_, routes)| !routes.is_empty())
+            .flat_map(|(feed, routes)| {
+                [
+                    bustime::fetch_bulletins(&self.upstream, feed, routes).boxed(),
+                    bustime::fetch_detours(&self.upstream, feed, routes).boxed(),
+                ]
+            });
+
+        let mut fetched = Vec::new();
+        let mut complete = true;
+        for result in join_all(calls).await {
+            match result {
+                Ok(alerts) => fetched.extend(alerts),
+                Err(e) => {
+                    println!("Alert refresh failed: {:?}", e);
+                    complete = false;
+                }
+            }
+        }
+
+        self.known
+            .lock()
+            .unwrap()
+            .merge(fetched, complete, Utc::now());
+    }
+
+    // the alerts in effect at `now`
+    pub fn current(&self, now: DateTime<Utc>) -> Vec<Alert> {
+        self.known.lock().unwrap().current(now)
+    }
+
+    // adds the alerts in effect at `now` to the route groups they concern
+    pub fn attach(&self, data: &mut FrontendResponse, now: DateTime<Utc>) {
+        attach(&self.current(now), data);
+    }
+}
+
+impl Known {
+    fn merge(&mut self, fetched: Vec<Alert>, complete: bool, now: DateTime<Utc>) {
+        // anything PRT didn't mention this time is over, if we asked about everything
+        if complete {
+            self.alerts.clear();
+        }
+        // an alert's latest routes and stops replace the ones it was last seen with
+        let mut updated: Vec<Seen> = Vec::new();
+        for alert in fetched {
+            match updated.iter_mut().find(|s| s.alert.id == alert.id) {
+                Some(seen) => combine(&mut seen.alert, alert),
+                None => updated.push(Seen {
+                    alert,
+                    seen_at: now,
+                }),
+            }
+        }
+        self.alerts
+            .retain(|old| !updated.iter().any(|new| new.alert.id == old.alert.id));
+        self.alerts.extend(updated);
+        self.fetched_at = Some(now);
+    }
+
+    fn current(&self, now: DateTime<Utc>) -> Vec<Alert> {
+        let expired = now - TimeDelta::seconds(ALERT_EXPIRY_SECONDS);
+        self.alerts
+            .iter()
+            .filter(|seen| seen.seen_at > expired)
+            .map(|seen| &seen.alert)
+            .filter(|alert| alert.starts_at.is_none_or(|at| at <= now))
+            .filter(|alert| alert.ends_at.is_none_or(|at| at > now))
+            .cloned()
+            .collect()
+    }
+}
+
+// the same alert as returned for different routes or feeds
+fn combine(into: &mut Alert, other: Alert) {
+    let scopes = std::mem::take(&mut into.applies_to);
+    into.applies_to = AlertScope::list(scopes.into_iter().chain(other.applies_to));
+}
+
+fn attach(alerts: &[Alert], data: &mut FrontendResponse) {
+    for (stop, groups) in data.iter_mut() {
+        for group in groups.iter_mut() {
+            group.alerts = alerts
+                .iter()
+                .filter(|alert| {
+                    alert
+                        .applies_to
+                        .iter()
+                        .any(|scope| scope.matches(&group.route, stop))
+                })
+                .cloned()
+                .collect();
+        }
+    }
+}
+
+pub async fn get_alerts(State(state): State<AppState>) -> Result<Json<AlertsResponse>, AppError> {
+    let alerts = state.alerts.as_ref().ok_or(AppError::NotAvailable(
+        "service alerts need the BusTime source",
+    ))?;
+
+    let known = alerts.known.lock().unwrap();
+    Ok(Json(AlertsResponse {
+        fetched_at: known.fetched_at,
+        alerts: known.current(Utc::now()),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::{self, testing::arrival};
+
+    // `applies_to` is (route, stop) pairs, "" for any
+    fn alert(id: &str, applies_to: &[(&str, &str)]) -> Alert {
+        let some = |value: &str| Some(value.to_string()).filter(|v| !v.is_empty());
+        Alert {
+            id: id.to_string(),
+            kind: AlertKind::Bulletin,
+            title: id.to_string(),
+            detail: String::new(),
+            applies_to: AlertScope::list(applies_to.iter().map(|&(route, stop)| AlertScope {
+                route: some(route),
+                stop: some(stop),
+            })),
+            starts_at: None,
+            ends_at: None,
+        }
+    }
+
+    fn ids(alerts: &[Alert]) -> Vec<&str> {
+        alerts.iter().map(|a| a.id.as_str()).collect()
+    }
+
+    // "route@stop", with "*" for any
+    fn scopes(alert: &Alert) -> Vec<String> {
+        alert
+            .applies_to
+            .iter()
+            .map(|scope| {
+                format!(
+                    "{}@{}",
+                    scope.route.as_deref().unwrap_or("*"),
+                    scope.stop.as_deref().unwrap_or("*")
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn merges_duplicates_and_expires_alerts() {
+        let now = Utc::now();
+        let mut known = Known::default();
+        known.merge(
+            vec![
+                alert("bulletin:a", &[("61C", "7117")]),
+                alert("bulletin:a", &[("61D", "4407"), ("61C", "7117")]),
+                alert("bulletin:b", &[("67", "")]),
+                alert("bulletin:b", &[("69", "4407")]),
+                alert("bulletin:c", &[("58", "")]),
+            ],
+            true,
+            now,
+        );
+        let current = known.current(now);
+        assert_eq!(ids(&current), ["bulletin:a", "bulletin:b", "bulletin:c"]);
+        assert_eq!(scopes(&current[0]), ["61C@7117", "61D@4407"]);
+        assert_eq!(scopes(&current[1]), ["67@*", "69@4407"]);
+
+        // a partial refresh keeps what it didn't hear about, for a while
+        let later = now + TimeDelta::minutes(10);
+        known.merge(vec![alert("bulletin:b", &[("67", "")])], false, later);
+        let current = known.current(later);
+        assert_eq!(ids(&current), ["bulletin:a", "bulletin:c", "bulletin:b"]);
+        assert_eq!(scopes(&current[2]), ["67@*"]);
+        assert_eq!(
+            ids(&known.current(now + TimeDelta::minutes(65))),
+            ["bulletin:b"]
+        );
+
+        // a complete one drops it straight away
+        known.merge(vec![alert("bulletin:c", &[("58", "")])], true, later);
+        assert_eq!(ids(&known.current(later)), ["bulletin:c"]);
+
+        let mut ended = alert("detour:1", &[("58", "")]);
+        ended.ends_at = Some((now - TimeDelta::minutes(1)).fixed_offset());
+        let mut upcoming = alert("detour:2", &[("58", "")]);
+        upcoming.starts_at = Some((now + TimeDelta::hours(1)).fixed_offset());
+        known.merge(vec![ended, upcoming], true, now);
+        assert!(known.current(now).is_empty());
+    }
+
+    #[test]
+    fn attaches_alerts_by_route_and_stop() {
+        let mut data = source::group(vec![
+            arrival("7117", "61C", "McKeesport", 5),
+            arrival("4407", "61C", "McKeesport", 5),
+            arrival("4407", "67", "Monroeville", 5),
+            arrival("7117", "67", "Monroeville", 5),
+        ]);
+        attach(
+            &[
+                alert("bulletin:everywhere", &[("61C", "")]),
+                alert("bulletin:one-stop", &[("61C", "4407")]),
+                alert("bulletin:stop-moved", &[("", "7117")]),
+                // one entry route-only, another stop-only: the 67 everywhere, anything at 4407,
+                // but not the 61C at 7117
+                alert("bulletin:mixed", &[("67", ""), ("", "4407")]),
+                alert("bulletin:system", &[]),
+            ],
+            &mut data,
+        );
+
+        let alerts = |stop: &str, route: &str| {
+            let group = data[stop].iter().find(|g| g.route == route).unwrap();
+            ids(&group.alerts)
+                .into_iter()
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            alerts("7117", "61C"),
+            [
+                "bulletin:everywhere",
+                "bulletin:stop-moved",
+                "bulletin:system"
+            ]
+        );
+        assert_eq!(
+            alerts("4407", "61C"),
+            [
+                "bulletin:everywhere",
+                "bulletin:one-stop",
+                "bulletin:mixed",
+                "bulletin:system"
+            ]
+        );
+        assert_eq!(alerts("4407", "67"), ["bulletin:mixed", "bulletin:system"]);
+        assert_eq!(
+            alerts("7117", "67"),
+            ["bulletin:stop-moved", "bulletin:mixed", "bulletin:system"]
+        );
+    }
+}
@@ -0,0 +1,305 @@
+// daily request budget for the BusTime key
+// every request to PRT is counted against the key's daily cap, the count survives restarts
+// (BUDGET_STATE_PATH), and the poller spaces its polls so the cap lasts until midnight
+// the file is written off the async threads, at most once every SAVE_DELAY, so a crash can
+// lose the last few seconds of the count
+
+use crate::CACHE_DURATION_SECONDS;
+use crate::local_time::TIMEZONE;
+use chrono::{DateTime, Datelike, NaiveDate, TimeDelta, Timelike, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+// polling intervals before the budget is taken into account
+const PEAK_INTERVAL: Duration = Duration::from_secs(15);
+const NORMAL_INTERVAL: Duration = Duration::from_secs(CACHE_DURATION_SECONDS);
+const NIGHT_INTERVAL: Duration = Duration::from_secs(120);
+// nobody is looking at any sign
+pub const IDLE_INTERVAL: Duration = Duration::from_secs(120);
+
+// weekday rush hours, local time (start hour inclusive, end hour exclusive)
+const PEAK_HOURS: [(u32, u32); 2] = [(7, 10), (15, 19)];
+// late night hours, local time
+const NIGHT_HOURS: (u32, u32) = (0, 5);
+
+// how long a change to the count waits before it is written out, with any that follow it
+const SAVE_DELAY: Duration = Duration::from_secs(5);
+
+pub struct Budget {
+    daily_limit: u64,
+    key_id: String,
+    path: Arc<str>,
+    state: Arc<Mutex<BudgetFile>>,
+    // a save is scheduled and hasn't read the count yet
+    save_pending: Arc<AtomicBool>,
+}
+
+// on disk, one entry per key so switching keys doesn't carry a count over
+#[derive(Serialize, Deserialize, Default)]
+struct BudgetFile {
+    keys: HashMap<String, DayCount>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct DayCount {
+    day: NaiveDate,
+    used: u64,
+}
+
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct BudgetStatus {
+    pub used: u64,
+    pub limit: u64,
+}
+
+impl Budget {
+    // `key` is only hashed, the file never contains the key itself
+    pub fn load(key: &str, daily_limit: u64, path: String) -> Self {
+        let state = match fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+                println!("Ignoring unreadable budget file {}: {}", path, e);
+                BudgetFile::default()
+            }),
+            Err(_) => BudgetFile::default(),
+        };
+
+        Budget {
+            daily_limit,
+            key_id: format!("{:016x}", fnv1a(key.as_bytes())),
+            path: path.into(),
+            state: Arc::new(Mutex::new(state)),
+            save_pending: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    // counts one request against today's budget, or false (counting nothing) if it's used up
+    pub fn try_consume(&self) -> bool {
+        self.try_consume_at(Utc::now())
+    }
+
+    fn try_consume_at(&self, now: DateTime<Utc>) -> bool {
+        let today = local_date(now);
+        {
+            let mut state = self.state.lock().unwrap();
+            let count = state.keys.entry(self.key_id.clone()).or_insert(DayCount {
+                day: today,
+                used: 0,
+            });
+            if count.day != today {
+                *count = DayCount {
+                    day: today,
+                    used: 0,
+                };
+            }
+            if count.used >= self.daily_limit {
+                return false;
+            }
+            count.used += 1;
+        }
+        self.schedule_save();
+        true
+    }
+
+    fn schedule_save(&self) {
+        if self.save_pending.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let state = self.state.clone();
+        let save_pending = self.save_pending.clone();
+        let path = self.path.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(SAVE_DELAY).await;
+            // anything counted after this point schedules another save
+            save_pending.store(false, Ordering::Release);
+            let json = serde_json::to_string(&*state.lock().unwrap());
+            let written = match json {
+                Ok(json) => tokio::task::spawn_blocking(move || save(&path, &json))
+                    .await
+                    .unwrap_or_else(|e| Err(e.to_string())),
+                Err(e) => Err(e.to_string()),
+            };
+            if let Err(e) = written {
+                println!("Could not save request budget: {}", e);
+            }
+        });
+    }
+
+    pub fn status(&self) -> BudgetStatus {
+        BudgetStatus {
+            used: self.used_on(local_date(Utc::now())),
+            limit: self.daily_limit,
+        }
+    }
+
+    fn used_on(&self, day: NaiveDate) -> u64 {
+        let state = self.state.lock().unwrap();
+        state
+            .keys
+            .get(&self.key_id)
+            .filter(|count| count.day == day)
+            .map(|count| count.used)
+            .unwrap_or(0)
+    }
+
+    // time until the next poll: quick at rush hour, slow at night or with nobody watching,
+    // and never so quick that the rest of today's budget runs out before midnight
+    pub fn next_interval(
+        &self,
+        now: DateTime<Utc>,
+        idle: bool,
+        requests_per_poll: u64,
+    ) -> Duration {
+        let now = now.with_timezone(&TIMEZONE);
+        let hour = now.hour();
+        let weekday = !matches!(now.weekday(), Weekday::Sat | Weekday::Sun);
+
+        let desired = if idle {
+            IDLE_INTERVAL
+        } else if (NIGHT_HOURS.0..NIGHT_HOURS.1).contains(&hour) {
+            NIGHT_INTERVAL
+        } else if weekday && PEAK_HOURS.iter().any(|(s, e)| (*s..*e).contains(&hour)) {
+            PEAK_INTERVAL
+        } else {
+            NORMAL_INTERVAL
+        };
+
+        let midnight = (now.date_naive() + TimeDelta::days(1))
+            .and_hms_opt(0, 0, 0)
+            .expect("midnight exists");
+        let seconds_left = (midnight - now.naive_local()).num_seconds().max(1) as u64;
+
+        let remaining_polls = self
+            .daily_limit
+            .saturating_sub(self.used_on(now.date_naive()))
+            / requests_per_poll.max(1);
+        if remaining_polls == 0 {
+            // nothing left, wait for the count to reset
+            return Duration::from_secs(seconds_left);
+        }
+
+        desired.max(Duration::from_secs(seconds_left / remaining_polls))
+    }
+}
+
+fn local_date(now: DateTime<Utc>) -> NaiveDate {
+    now.with_timezone(&TIMEZONE).date_naive()
+}
+
+// written through a temporary file so a crash mid-write can't lose the count
+fn save(path: &str, json: &str) -> Result<(), String> {
+    let tmp = format!("{}.tmp", path);
+    fs::write(&tmp, json)
+        .and_then(|_| fs::rename(&tmp, path))
+        .map_err(|e| format!("{}: {}", path, e))
+}
+
+// stable across builds, unlike std's hasher
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget(limit: u64, name: &str) -> Budget {
+        let path =
+            std::env::temp_dir().join(format!("budget-test-{}-{}.json", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        Budget::load("key", limit, path.to_string_lossy().into_owned())
+    }
+
+    // local time, written with its offset
+    fn at(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_str(text, "%Y-%m-%d %H:%M %z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[tokio::test]
+    async fn consumes_until_the_limit_and_resets_each_day() {
+        let budget = budget(2, "limit");
+        let day = at("2026-10-19 12:00 -0400");
+        assert!(budget.try_consume_at(day));
+        assert!(budget.try_consume_at(day));
+        assert!(!budget.try_consume_at(day));
+        assert_eq!(
+            budget.used_on(local_date(day)),
+            2,
+            "refusals aren't counted"
+        );
+
+        // just after local midnight, still the 19th in UTC
+        let next = at("2026-10-20 00:01 -0400");
+        assert!(budget.try_consume_at(next));
+        assert_eq!(budget.used_on(local_date(next)), 1);
+    }
+
+    #[test]
+    fn counts_survive_a_restart_per_key() {
+        let first = budget(10, "restart");
+        let today = local_date(Utc::now());
+        first.state.lock().unwrap().keys.insert(
+            first.key_id.clone(),
+            DayCount {
+                day: today,
+                used: 7,
+            },
+        );
+        let json = serde_json::to_string(&*first.state.lock().unwrap()).unwrap();
+        save(&first.path, &json).unwrap();
+
+        let reloaded = Budget::load("key", 10, first.path.to_string());
+        assert_eq!(reloaded.status().used, 7);
+        let other_key = Budget::load("other", 10, first.path.to_string());
+        assert_eq!(other_key.status().used, 0);
+        fs::remove_file(&*first.path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn spaces_polls_by_time_of_day_and_budget() {
+        let roomy = budget(1_000_000, "interval");
+        let monday = |time: &str| at(&format!("2026-10-19 {} -0400", time));
+
+        assert_eq!(
+            roomy.next_interval(monday("08:00"), false, 1),
+            PEAK_INTERVAL
+        );
+        assert_eq!(
+            roomy.next_interval(monday("12:00"), false, 1),
+            NORMAL_INTERVAL
+        );
+        assert_eq!(
+            roomy.next_interval(monday("02:00"), false, 1),
+            NIGHT_INTERVAL
+        );
+        assert_eq!(roomy.next_interval(monday("08:00"), true, 1), IDLE_INTERVAL);
+        let saturday = at("2026-10-24 08:00 -0400");
+        assert_eq!(roomy.next_interval(saturday, false, 1), NORMAL_INTERVAL);
+
+        // 10 requests left for the 4 hours to midnight, 2 per poll: one poll every 48 minutes
+        let tight = budget(10, "tight");
+        assert_eq!(
+            tight.next_interval(monday("20:00"), false, 2),
+            Duration::from_secs(48 * 60)
+        );
+        // none left: wait for midnight
+        for _ in 0..10 {
+            tight.try_consume_at(monday("20:00"));
+        }
+        assert_eq!(
+            tight.next_interval(monday("20:00"), false, 2),
+            Duration::from_secs(4 * 60 * 60)
+        );
+    }
+}
@@ -0,0 +1,1086 @@
+// PRT's BusTime v3 API, the sign's original feed
+// getpredictions takes at most MAX_STOPS_PER_REQUEST stops per call, so stops are fetched in
+// batches, all in parallel; every call goes through Upstream and counts against the key's
+// daily budget, which also sets how often this source is polled (see budget.rs)
+// BusTime serves several real-time feeds (buses, light rail, ...) that are asked separately,
+// each for the stops configured for it, and merged into one update
+// getvehicles is here too, for /vehicles (see vehicles.rs), getservicebulletins and
+// getdetours for alerts (see alerts.rs), getroutes for route names and colors (see routes.rs),
+// and getstops and getdirections for stop names and places (see stops.rs)
+
+use crate::alerts::{Alert, AlertKind, AlertScope};
+use crate::config::{FeedConfig, StopConfig};
+use crate::local_time;
+use crate::prt_errors::PrtErrorKind;
+use crate::routes::RouteInfo;
+use crate::source::{Arrival, ArrivalDetails, ArrivalKind, Mode, PredictionSource, SourceUpdate};
+use crate::stops::StopDetails;
+use crate::upstream::{ApiKey, Upstream};
+use crate::vehicles::Vehicle;
+use crate::{AppError, FrontendResponse, PrtMessage, StopFailure};
+use chrono::Utc;
+use futures_util::future::{BoxFuture, join_all};
+use serde::{Deserialize, Deserializer, de::DeserializeOwned};
+use std::{collections::BTreeSet, fmt::Display, str::FromStr, sync::Arc, time::Duration};
+
+const TIME_RES: &str = "s"; // resolution of time data (seconds)
+// the feed asked for every stop if none are configured
+const DEFAULT_FEED: &str = "Port Authority Bus";
+// getpredictions takes at most this many stop IDs per call
+const MAX_STOPS_PER_REQUEST: usize = 10;
+// and getvehicles and getservicebulletins this many routes (getdetours takes only one)
+const MAX_ROUTES_PER_REQUEST: usize = 10;
+
+// --- INCOMING DATA (From API) ---
+#[derive(Deserialize, Debug)]
+struct PrtResponse {
+    #[serde(rename = "bustime-response")]
+    response: PrtBody,
+}
+
+#[derive(Deserialize, Debug)]
+struct PrtBody {
+    #[serde(rename = "prd", default)]
+    predictions: Option<Vec<PrtPrediction>>,
+    #[serde(rename = "error", default)]
+    api_error: Option<Vec<PrtError>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PrtError {
+    msg: String,
+    #[serde(default)]
+    stpid: Option<String>,
+    #[serde(default)]
+    rt: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PrtPrediction {
+    rt: String,
+    des: String,
+    stpid: String,
+    vid: String,
+    tmstmp: String,
+    prdtm: String,
+    #[serde(default)]
+    psgld: String,
+    #[serde(default)]
+    rtdir: String,
+    #[serde(default)]
+    stpnm: String,
+    // feet to the stop
+    #[serde(deserialize_with = "optional_number", default)]
+    dstp: Option<u32>,
+    #[serde(default)]
+    dly: bool,
+    #[serde(default)]
+    prdctdn: String,
+    #[serde(default)]
+    tatripid: String,
+    #[serde(default)]
+    tablockid: String,
+    #[serde(rename = "dyn", deserialize_with = "optional_number", default)]
+    dynamic: Option<u8>,
+}
+
+// any other call's response: its data next to the same error list as getpredictions
+#[derive(Deserialize, Debug)]
+struct LookupResponse<T> {
+    #[serde(rename = "bustime-response")]
+    response: LookupBody<T>,
+}
+
+#[derive(Deserialize, Debug)]
+struct LookupBody<T> {
+    #[serde(flatten)]
+    data: T,
+    #[serde(rename = "error", default)]
+    api_error: Option<Vec<PrtError>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct VehicleList {
+    #[serde(rename = "vehicle", default)]
+    vehicles: Vec<PrtVehicle>,
+}
+
+// BusTime sends some numbers as JSON strings
+#[derive(Deserialize, Debug)]
+struct PrtVehicle {
+    vid: String,
+    tmstmp: String,
+    #[serde(deserialize_with = "number")]
+    lat: f64,
+    #[serde(deserialize_with = "number")]
+    lon: f64,
+    #[serde(deserialize_with = "number")]
+    hdg: u16,
+    #[serde(deserialize_with = "number")]
+    pid: u64,
+    rt: String,
+    des: String,
+    #[serde(default)]
+    dly: bool,
+    #[serde(deserialize_with = "number", default)]
+    spd: u32,
+}
+
+#[derive(Deserialize, Debug)]
+struct RouteList {
+    #[serde(default)]
+    routes: Vec<PrtRoute>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PrtRoute {
+    rt: String,
+    #[serde(default)]
+    rtnm: String,
+    // "#rrggbb"
+    #[serde(default)]
+    rtclr: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct StopList {
+    #[serde(default)]
+    stops: Vec<PrtStop>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PrtStop {
+    stpid: String,
+    stpnm: String,
+    #[serde(deserialize_with = "number")]
+    lat: f64,
+    #[serde(deserialize_with = "number")]
+    lon: f64,
+}
+
+#[derive(Deserialize, Debug)]
+struct DirectionList {
+    #[serde(default)]
+    directions: Vec<PrtDirection>,
+}
+
+// `id` is what getstops takes, `name` what riders see ("INBOUND", "OUTBOUND")
+#[derive(Deserialize, Debug)]
+struct PrtDirection {
+    id: String,
+    #[serde(default)]
+    name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct BulletinList {
+    #[serde(rename = "sb", default)]
+    bulletins: Vec<PrtBulletin>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PrtBulletin {
+    // the bulletin's name, unique among current bulletins
+    nm: String,
+    #[serde(default)]
+    sbj: String,
+    // full text, may be HTML
+    #[serde(default)]
+    dtl: String,
+    // short text
+    #[serde(default)]
+    brf: String,
+    // what it applies to, empty fields meaning "any"
+    #[serde(default)]
+    srvc: Vec<PrtBulletinService>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PrtBulletinService {
+    #[serde(default)]
+    rt: String,
+    #[serde(default)]
+    stpid: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct DetourList {
+    #[serde(rename = "dtrs", default)]
+    detours: Vec<PrtDetour>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PrtDetour {
+    id: String,
+    // each change to a detour is a new version under the same id
+    #[serde(deserialize_with = "number")]
+    ver: u32,
+    // 1 active, 0 canceled
+    #[serde(deserialize_with = "number")]
+    st: u8,
+    #[serde(default)]
+    desc: String,
+    #[serde(default)]
+    rtdirs: Vec<PrtDetourRoute>,
+    #[serde(default)]
+    startdt: String,
+    #[serde(default)]
+    enddt: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct PrtDetourRoute {
+    rt: String,
+}
+
+pub struct BusTime {
+    upstream: Arc<Upstream>,
+    feeds: Vec<FeedConfig>,
+}
+
+// one getpredictions call
+struct Batch<'a> {
+    feed: &'a FeedConfig,
+    stops: Vec<&'a str>,
+}
+
+// and what it returned
+type BatchResult = Result<(Vec<Arrival>, Vec<PrtMessage>), AppError>;
+
+impl BusTime {
+    pub fn new(upstream: Arc<Upstream>, feeds: &[FeedConfig]) -> Self {
+        BusTime {
+            upstream,
+            feeds: self::feeds(feeds),
+        }
+    }
+
+    // the result is only returned once every batch is in, so a poll is never half old and half new
+    async fn fetch_all(&self, stops: &[StopConfig]) -> Result<SourceUpdate, AppError> {
+        println!("Fetching from API");
+        let batches = batches(&self.feeds, stops);
+        let results = join_all(batches.iter().map(|batch| self.fetch_batch(batch))).await;
+        merge(&batches, results)
+    }
+
+    async fn fetch_batch(&self, batch: &Batch<'_>) -> BatchResult {
+        let stop_ids = batch.stops.join(",");
+
+        let raw_text = self
+            .upstream
+            .get_text(
+                "getpredictions",
+                &[
+                    ("stpid", stop_ids.as_str()),
+                    ("tmres", TIME_RES),
+                    ("rtpidatafeed", batch.feed.name.as_str()),
+                ],
+            )
+            .await
+            .map_err(AppError::UpstreamError)?;
+
+        parse_predictions(&raw_text, self.upstream.api_key(), batch.feed.mode)
+    }
+}
+
+// the feeds to ask, the bus feed for every stop if none are configured
+pub fn feeds(configured: &[FeedConfig]) -> Vec<FeedConfig> {
+    if configured.is_empty() {
+        vec![FeedConfig {
+            name: DEFAULT_FEED.to_string(),
+            mode: Mode::Bus,
+            stops: Vec::new(),
+        }]
+    } else {
+        configured.to_vec()
+    }
+}
+
+// for each feed, the routes of its mode predicted at its stops, sorted
+pub fn routes_by_feed(feeds: &[FeedConfig], data: &FrontendResponse) -> Vec<Vec<String>> {
+    feeds
+        .iter()
+        .map(|feed| {
+            let routes: BTreeSet<&str> = data
+                .iter()
+                .filter(|(stop, _)| feed.stops.is_empty() || feed.stops.contains(stop))
+                .flat_map(|(_, groups)| groups)
+                .filter(|group| group.mode == feed.mode)
+                .map(|group| group.route.as_str())
+                .collect();
+            routes.into_iter().map(str::to_string).collect()
+        })
+        .collect()
+}
+
+// every vehicle on `routes` of `feed`, in as few getvehicles calls as possible
+pub async fn fetch_vehicles(
+    upstream: &Upstream,
+    feed: &FeedConfig,
+    routes: &[String],
+) -> Result<Vec<Vehicle>, AppError> {
+    let calls = routes
+        .chunks(MAX_ROUTES_PER_REQUEST)
+        .map(|batch| async move {
+            let raw_text = upstream
+                .get_text(
+                    "getvehicles",
+                    &[
+                        ("rt", batch.join(",").as_str()),
+                        ("tmres", TIME_RES),
+                        ("rtpidatafeed", feed.name.as_str()),
+                    ],
+                )
+                .await
+                .map_err(AppError::UpstreamError)?;
+            parse_vehicles(&raw_text, upstream.api_key(), feed.mode)
+        });
+
+    let mut vehicles = Vec::new();
+    for result in join_all(calls).await {
+        vehicles.extend(result?);
+    }
+    Ok(vehicles)
+}
+
+// every route `feed` serves
+pub async fn fetch_routes(
+    upstream: &Upstream,
+    feed: &FeedConfig,
+) -> Result<Vec<RouteInfo>, AppError> {
+    let raw_text = upstream
+        .get_text("getroutes", &[("rtpidatafeed", feed.name.as_str())])
+        .await
+        .map_err(AppError::UpstreamError)?;
+    parse_routes(&raw_text, upstream.api_key(), feed.mode)
+}
+
+// the name and place of each of `stops` that `feed` knows
+pub async fn fetch_stops(
+    upstream: &Upstream,
+    feed: &FeedConfig,
+    stops: &[&str],
+) -> Result<Vec<StopDetails>, AppError> {
+    let calls = stops.chunks(MAX_STOPS_PER_REQUEST).map(|batch| async move {
+        let raw_text = upstream
+            .get_text(
+                "getstops",
+                &[
+                    ("stpid", batch.join(",").as_str()),
+                    ("rtpidatafeed", feed.name.as_str()),
+                ],
+            )
+            .await
+            .map_err(AppError::UpstreamError)?;
+        parse_stops(&raw_text, upstream.api_key())
+    });
+
+    let mut details = Vec::new();
+    for result in join_all(calls).await {
+        details.extend(result?);
+    }
+    Ok(details)
+}
+
+// every stop `route` of `feed` serves, with the direction it serves it in
+pub async fn fetch_route_stops(
+    upstream: &Upstream,
+    feed: &FeedConfig,
+    route: &str,
+) -> Result<Vec<(StopDetails, String)>, AppError> {
+    let raw_text = upstream
+        .get_text(
+            "getdirections",
+            &[("rt", route), ("rtpidatafeed", feed.name.as_str())],
+        )
+        .await
+        .map_err(AppError::UpstreamError)?;
+    let list: DirectionList = parse_lookup(&raw_text, upstream.api_key())?;
+
+    let calls = list.directions.into_iter().map(|direction| async move {
+        let raw_text = upstream
+            .get_text(
+                "getstops",
+                &[
+                    ("rt", route),
+                    ("dir", direction.id.as_str()),
+                    ("rtpidatafeed", feed.name.as_str()),
+                ],
+            )
+            .await
+            .map_err(AppError::UpstreamError)?;
+        let name = if direction.name.is_empty() {
+            direction.id
+        } else {
+            direction.name
+        };
+        let stops = parse_stops(&raw_text, upstream.api_key())?;
+        Ok::<_, AppError>(stops.into_iter().map(move |stop| (stop, name.clone())))
+    });
+
+    let mut stops = Vec::new();
+    for result in join_all(calls).await {
+        stops.extend(result?);
+    }
+    Ok(stops)
+}
+
+impl PredictionSource for BusTime {
+    fn name(&self) -> &'static str {
+        "bustime"
+    }
+
+    fn fetch<'a>(
+        &'a self,
+        stops: &'a [StopConfig],
+    ) -> BoxFuture<'a, Result<SourceUpdate, AppError>> {
+        Box::pin(self.fetch_all(stops))
+    }
+
+    fn poll_interval(&self, idle: bool, stops: usize) -> Duration {
+        let requests: usize = self
+            .feeds
+            .iter()
+            .map(|feed| match feed.stops.len() {
+                0 => stops,
+                n => n,
+            })
+            .map(|n| n.div_ceil(MAX_STOPS_PER_REQUEST))
+            .sum();
+        self.upstream
+            .budget()
+            .next_interval(Utc::now(), idle, requests as u64)
+    }
+}
+
+// bulletins for any of `routes` of `feed`, some possibly more than once
+pub async fn fetch_bulletins(
+    upstream: &Upstream,
+    feed: &FeedConfig,
+    routes: &[String],
+) -> Result<Vec<Alert>, AppError> {
+    let calls = routes
+        .chunks(MAX_ROUTES_PER_REQUEST)
+        .map(|batch| async move {
+            let raw_text = upstream
+                .get_text(
+                    "getservicebulletins",
+                    &[
+                        ("rt", batch.join(",").as_str()),
+                        ("rtpidatafeed", feed.name.as_str()),
+                    ],
+                )
+                .await
+                .map_err(AppError::UpstreamError)?;
+            parse_bulletins(&raw_text, upstream.api_key())
+        });
+
+    let mut alerts = Vec::new();
+    for result in join_all(calls).await {
+        alerts.extend(result?);
+    }
+    Ok(alerts)
+}
+
+// active detours on any of `routes` of `feed`, one call per route
+pub async fn fetch_detours(
+    upstream: &Upstream,
+    feed: &FeedConfig,
+    routes: &[String],
+) -> Result<Vec<Alert>, AppError> {
+    let calls = routes.iter().map(|route| async move {
+        let raw_text = upstream
+            .get_text(
+                "getdetours",
+                &[("rt", route.as_str()), ("rtpidatafeed", feed.name.as_str())],
+            )
+            .await
+            .map_err(AppError::UpstreamError)?;
+        parse_detours(&raw_text, upstream.api_key())
+    });
+
+    let mut alerts = Vec::new();
+    for result in join_all(calls).await {
+        alerts.extend(result?);
+    }
+    Ok(alerts)
+}
+
+// one update from every batch's result
+// a batch that fails only fails its own stops, unless every batch fails or PRT rejects the key
+fn merge(batches: &[Batch<'_>], results: Vec<BatchResult>) -> Result<SourceUpdate, AppError> {
+    let mut update = SourceUpdate::default();
+    let mut failed_batches = 0;
+    let mut first_error = None;
+    for (batch, result) in batches.iter().zip(results) {
+        match result {
+            Ok((arrivals, messages)) => {
+                update.arrivals.extend(arrivals);
+                update.messages.extend(messages);
+            }
+            // only errors that apply to every request get this far, see parse_predictions
+            Err(e @ AppError::PrtError(..)) => return Err(e),
+            Err(e) => {
+                println!(
+                    "Batch {} of {} failed: {:?}",
+                    batch.stops.join(","),
+                    batch.feed.name,
+                    e
+                );
+                for stop in &batch.stops {
+                    // a stop in several feeds is only reported once
+                    if !update.failures.iter().any(|f| f.stop == *stop) {
+                        update.failures.push(StopFailure {
+                            stop: stop.to_string(),
+                            error: e.clone(),
+                        });
+                    }
+                }
+                failed_batches += 1;
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+
+    match first_error {
+        Some(e) if failed_batches == batches.len() => Err(e),
+        _ => Ok(update),
+    }
+}
+
+// each feed's stops, split into groups small enough for one getpredictions call each
+fn batches<'a>(feeds: &'a [FeedConfig], stops: &'a [StopConfig]) -> Vec<Batch<'a>> {
+    let mut batches = Vec::new();
+    for feed in feeds {
+        let feed_stops: Vec<&str> = stops
+            .iter()
+            .map(|s| s.id.as_str())
+            .filter(|id| feed.stops.is_empty() || feed.stops.iter().any(|s| s == id))
+            .collect();
+        batches.extend(feed_stops.chunks(MAX_STOPS_PER_REQUEST).map(|chunk| Batch {
+            feed,
+            stops: chunk.to_vec(),
+        }));
+    }
+    batches
+}
+
+// everything after the HTTP request, split out so it can be tested without PRT
+pub fn parse_predictions(
+    raw_text: &str,
+    api_key: &ApiKey,
+    mode: Mode,
+) -> Result<(Vec<Arrival>, Vec<PrtMessage>), AppError> {
+    // redacted first, so neither parse errors nor PRT's messages can carry the key any further
+    let clean_text = api_key.redact(&raw_text.replace(r"\", "/"));
+    let prt_data: PrtResponse =
+        serde_json::from_str(&clean_text).map_err(|e| AppError::ParseError(e.to_string()))?;
+
+    let mut messages = Vec::new();
+    for err in prt_data.response.api_error.unwrap_or_default() {
+        let kind = PrtErrorKind::classify(&err.msg);
+        println!(
+            "PRT API Error [{}] (stop {}): {}",
+            kind.code(),
+            err.stpid.as_deref().unwrap_or("-"),
+            err.msg
+        );
+
+        // a bad key or PRT outage poisons the whole response, per-stop errors only their stop
+        if kind.fails_poll() {
+            return Err(AppError::PrtError(kind, err.msg));
+        }
+
+        messages.push(PrtMessage {
+            kind,
+            message: err.msg,
+            stop: err.stpid,
+            route: err.rt,
+        });
+    }
+
+    let mut arrivals = Vec::new();
+
+    if let Some(predictions) = prt_data.response.predictions {
+        let now = Utc::now();
+        for p in predictions.into_iter() {
+            // the prediction was made just before now, and the bus arrives after the prediction
+            let timestamp = local_time::parse_after(&p.tmstmp, now);
+            let arrival_at =
+                timestamp.and_then(|s| local_time::parse_after(&p.prdtm, s.with_timezone(&Utc)));
+            let (predicted_at, arrival_at) = match (timestamp, arrival_at) {
+                (Some(s), Some(a)) => (s, a),
+                _ => continue, // skip this iteration if bad time data
+                               // TODO: add some kind of internal warning here
+            };
+
+            arrivals.push(Arrival {
+                stop: p.stpid,
+                route: p.rt,
+                destination: p.des,
+                bus_id: p.vid,
+                predicted_at: predicted_at.fixed_offset(),
+                arrival_at: arrival_at.fixed_offset(),
+                capacity: p.psgld,
+                kind: ArrivalKind::Realtime,
+                mode,
+                details: ArrivalDetails {
+                    direction: non_empty(p.rtdir),
+                    stop_name: non_empty(p.stpnm),
+                    distance_feet: p.dstp,
+                    delayed: p.dly,
+                    countdown: non_empty(p.prdctdn),
+                    trip_id: non_empty(p.tatripid),
+                    block_id: non_empty(p.tablockid),
+                    dynamic_action: p.dynamic,
+                },
+            });
+        }
+    }
+
+    Ok((arrivals, messages))
+}
+
+// like parse_predictions, for calls other than getpredictions: errors that apply to the whole
+// call fail it, the rest (a route with nothing running, ...) are only logged
+fn parse_lookup<T: DeserializeOwned>(raw_text: &str, api_key: &ApiKey) -> Result<T, AppError> {
+    let clean_text = api_key.redact(&raw_text.replace(r"\", "/"));
+    let prt_data: LookupResponse<T> =
+        serde_json::from_str(&clean_text).map_err(|e| AppError::ParseError(e.to_string()))?;
+
+    for err in prt_data.response.api_error.unwrap_or_default() {
+        let kind = PrtErrorKind::classify(&err.msg);
+        if kind.fails_poll() {
+            return Err(AppError::PrtError(kind, err.msg));
+        }
+        println!(
+            "PRT API Error [{}] (route {}): {}",
+            kind.code(),
+            err.rt.as_deref().unwrap_or("-"),
+            err.msg
+        );
+    }
+    Ok(prt_data.response.data)
+}
+
+pub fn parse_vehicles(
+    raw_text: &str,
+    api_key: &ApiKey,
+    mode: Mode,
+) -> Result<Vec<Vehicle>, AppError> {
+    let list: VehicleList = parse_lookup(raw_text, api_key)?;
+    let now = Utc::now();
+    Ok(list
+        .vehicles
+        .into_iter()
+        .filter_map(|v| {
+            Some(Vehicle {
+                updated_at: local_time::parse_after(&v.tmstmp, now)?.fixed_offset(),
+                bus_id: v.vid,
+                route: v.rt,
+                destination: v.des,
+                lat: v.lat,
+                lon: v.lon,
+                heading: v.hdg,
+                speed: v.spd,
+                pattern_id: v.pid,
+                delayed: v.dly,
+                mode,
+            })
+        })
+        .collect())
+}
+
+pub fn parse_routes(
+    raw_text: &str,
+    api_key: &ApiKey,
+    mode: Mode,
+) -> Result<Vec<RouteInfo>, AppError> {
+    let list: RouteList = parse_lookup(raw_text, api_key)?;
+    Ok(list
+        .routes
+        .into_iter()
+        .map(|r| RouteInfo {
+            route: r.rt,
+            name: Some(r.rtnm).filter(|name| !name.is_empty()),
+            color: Some(r.rtclr).filter(|color| !color.is_empty()),
+            mode,
+        })
+        .collect())
+}
+
+pub fn parse_stops(raw_text: &str, api_key: &ApiKey) -> Result<Vec<StopDetails>, AppError> {
+    let list: StopList = parse_lookup(raw_text, api_key)?;
+    Ok(list
+        .stops
+        .into_iter()
+        .map(|s| StopDetails {
+            id: s.stpid,
+            name: s.stpnm,
+            lat: s.lat,
+            lon: s.lon,
+        })
+        .collect())
+}
+
+pub fn parse_bulletins(raw_text: &str, api_key: &ApiKey) -> Result<Vec<Alert>, AppError> {
+    let list: BulletinList = parse_lookup(raw_text, api_key)?;
+    Ok(list
+        .bulletins
+        .into_iter()
+        .map(|b| {
+            let detail = if b.brf.trim().is_empty() {
+                &b.dtl
+            } else {
+                &b.brf
+            };
+            Alert {
+                id: format!("bulletin:{}", b.nm),
+                kind: AlertKind::Bulletin,
+                title: if b.sbj.is_empty() {
+                    b.nm.clone()
+                } else {
+                    b.sbj.clone()
+                },
+                detail: plain_text(detail),
+                applies_to: AlertScope::list(b.srvc.iter().map(|s| AlertScope {
+                    route: non_empty(s.rt.clone()),
+                    stop: non_empty(s.stpid.clone()),
+                })),
+                starts_at: None,
+                ends_at: None,
+            }
+        })
+        .collect())
+}
+
+// the latest version of each detour, leaving out canceled ones
+pub fn parse_detours(raw_text: &str, api_key: &ApiKey) -> Result<Vec<Alert>, AppError> {
+    let list: DetourList = parse_lookup(raw_text, api_key)?;
+    let mut latest: Vec<PrtDetour> = Vec::new();
+    for detour in list.detours {
+        match latest.iter_mut().find(|d| d.id == detour.id) {
+            Some(known) if known.ver >= detour.ver => {}
+            Some(known) => *known = detour,
+            None => latest.push(detour),
+        }
+    }
+
+    Ok(latest
+        .into_iter()
+        .filter(|d| d.st == 1)
+        .map(|d| Alert {
+            id: format!("detour:{}", d.id),
+            kind: AlertKind::Detour,
+            title: d.desc.trim().to_string(),
+            detail: String::new(),
+            applies_to: AlertScope::list(d.rtdirs.iter().map(|r| AlertScope {
+                route: non_empty(r.rt.clone()),
+                stop: None,
+            })),
+            starts_at: local_time::parse_wall(&d.startdt).map(|t| t.fixed_offset()),
+            ends_at: local_time::parse_wall(&d.enddt).map(|t| t.fixed_offset()),
+        })
+        .collect())
+}
+
+// bulletin text without its HTML markup, on one line
+fn plain_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                text.push(' ');
+            }
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn non_empty(text: String) -> Option<String> {
+    Some(text).filter(|text| !text.is_empty())
+}
+
+// like `number`, but an empty string is no number at all
+fn optional_number<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(serde_json::Number),
+        Text(String),
+    }
+
+    let text = match Raw::deserialize(deserializer)? {
+        Raw::Number(n) => n.to_string(),
+        Raw::Text(s) => s,
+    };
+    match text.trim() {
+        "" => Ok(None),
+        text => text.parse().map(Some).map_err(serde::de::Error::custom),
+    }
+}
+
+// a number, or a string holding one
+fn number<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(serde_json::Number),
+        Text(String),
+    }
+
+    let text = match Raw::deserialize(deserializer)? {
+        Raw::Number(n) => n.to_string(),
+        Raw::Text(s) => s,
+    };
+    text.trim().parse().map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::source::testing::{InMemorySource, arrival};
+    use crate::testing;
+    use crate::upstream::UpstreamFailure;
+    use axum::{extract::State, response::IntoResponse};
+
+    fn feed(name: &str, mode: Mode, stops: &[&str]) -> FeedConfig {
+        FeedConfig {
+            name: name.to_string(),
+            mode,
+            stops: stops.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn batches_each_feed_over_its_own_stops() {
+        let stops: Vec<StopConfig> = (1..=12)
+            .map(|i| StopConfig {
+                id: i.to_string(),
+                label: None,
+                walk_time: None,
+                side: None,
+            })
+            .collect();
+        let feeds = [
+            feed(DEFAULT_FEED, Mode::Bus, &[]),
+            feed("Light Rail", Mode::Rail, &["3", "11"]),
+        ];
+
+        let batches = batches(&feeds, &stops);
+        let calls: Vec<(&str, String)> = batches
+            .iter()
+            .map(|b| (b.feed.name.as_str(), b.stops.join(",")))
+            .collect();
+        assert_eq!(
+            calls,
+            [
+                (DEFAULT_FEED, "1,2,3,4,5,6,7,8,9,10".to_string()),
+                (DEFAULT_FEED, "11,12".to_string()),
+                ("Light Rail", "3,11".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn a_failed_batch_only_fails_its_own_stops() {
+        let stops = Config::default().stops;
+        let feeds = [
+            feed(DEFAULT_FEED, Mode::Bus, &["7117"]),
+            feed("Light Rail", Mode::Rail, &["4407"]),
+        ];
+        let batches = batches(&feeds, &stops);
+        let update = merge(
+            &batches,
+            vec![
+                Ok((vec![arrival("7117", "61C", "McKeesport", 5)], Vec::new())),
+                Err(AppError::UpstreamError(UpstreamFailure::Timeout)),
+            ],
+        )
+        .unwrap();
+        let failed: Vec<&str> = update.failures.iter().map(|f| f.stop.as_str()).collect();
+        assert_eq!(failed, ["4407"]);
+
+        // and the stops that did come back are still served, with the rest named in a header
+        let source = Arc::new(InMemorySource::new(update.arrivals));
+        *source.failures.lock().unwrap() = update.failures;
+        let response = crate::get_predictions(State(testing::state(source).await))
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(response.headers()["x-failed-stops"], "4407");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let data: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(data["7117"][0]["route"], "61C");
+        assert!(data.get("4407").is_none());
+    }
+
+    #[test]
+    fn every_failed_batch_fails_the_poll() {
+        let stops = Config::default().stops;
+        let feeds = [feed(DEFAULT_FEED, Mode::Bus, &[])];
+        let result = merge(
+            &batches(&feeds, &stops),
+            vec![Err(AppError::UpstreamError(UpstreamFailure::Timeout))],
+        );
+        assert!(matches!(result, Err(AppError::UpstreamError(_))));
+    }
+
+    #[test]
+    fn arrivals_take_the_feed_mode() {
+        let raw = r#"{"bustime-response": {"prd": [{"rt": "RED", "des": "South Hills Village",
+            "stpid": "8161", "vid": "4301", "tmstmp": "20261018 08:00:00",
+            "prdtm": "20261018 08:04:00"}]}}"#;
+        let (arrivals, _) =
+            parse_predictions(raw, &ApiKey::new(String::new()), Mode::Rail).unwrap();
+        assert_eq!(arrivals[0].mode, Mode::Rail);
+    }
+
+    #[test]
+    fn passes_prediction_details_through() {
+        let raw = r#"{"bustime-response": {"prd": [
+            {"rt": "61C", "des": "McKeesport", "stpid": "7117", "vid": "3201",
+                "tmstmp": "20261018 08:00:00", "prdtm": "20261018 08:05:00",
+                "rtdir": "OUTBOUND", "stpnm": "Forbes Ave opp Morewood Ave", "dstp": 11088,
+                "dly": true, "prdctdn": "DLY", "tatripid": "12345", "tablockid": "061C-123",
+                "dyn": 0},
+            {"rt": "61C", "des": "McKeesport", "stpid": "7117", "vid": "3202",
+                "tmstmp": "20261018 08:00:00", "prdtm": "20261018 08:20:00",
+                "dstp": "", "tatripid": "", "dyn": "4"}]}}"#;
+        let (arrivals, _) = parse_predictions(raw, &ApiKey::new(String::new()), Mode::Bus).unwrap();
+
+        let data = crate::source::group(arrivals);
+        let group = &data["7117"][0];
+        assert_eq!(group.direction.as_deref(), Some("OUTBOUND"));
+        assert_eq!(
+            group.stop_name.as_deref(),
+            Some("Forbes Ave opp Morewood Ave")
+        );
+
+        let (first, second) = (&group.arrivals[0], &group.arrivals[1]);
+        assert_eq!(first.distance_feet, Some(11088));
+        assert!(first.delayed);
+        assert_eq!(first.countdown.as_deref(), Some("DLY"));
+        assert_eq!(first.trip_id.as_deref(), Some("12345"));
+        assert_eq!(first.block_id.as_deref(), Some("061C-123"));
+        assert_eq!(first.dynamic_action, Some(0));
+        assert_eq!(
+            (second.distance_feet, second.trip_id.as_deref()),
+            (None, None)
+        );
+        assert_eq!(second.dynamic_action, Some(4));
+    }
+
+    #[test]
+    fn parses_vehicles_with_numbers_as_strings() {
+        let raw = r#"{"bustime-response": {"vehicle": [{"vid": "3201",
+            "tmstmp": "20261018 08:00:00", "lat": "40.4443", "lon": -79.9436, "hdg": "93",
+            "pid": 4552, "rt": "61C", "des": "McKeesport", "dly": true, "spd": 14}],
+            "error": [{"rt": "61D", "msg": "No data found for parameter"}]}}"#;
+
+        let vehicles = parse_vehicles(raw, &ApiKey::new(String::new()), Mode::Bus).unwrap();
+        assert_eq!(vehicles.len(), 1, "a route without vehicles isn't an error");
+        let bus = &vehicles[0];
+        assert_eq!((bus.lat, bus.lon), (40.4443, -79.9436));
+        assert_eq!((bus.heading, bus.speed, bus.pattern_id), (93, 14, 4552));
+        assert!(bus.delayed);
+    }
+
+    #[test]
+    fn parses_bulletins_as_plain_text() {
+        let raw = r#"{"bustime-response": {"sb": [{"nm": "Smithfield closure",
+            "sbj": "Smithfield St closed", "dtl": "<p>Buses use <b>Wood&nbsp;St</b>&amp;  Liberty</p>",
+            "brf": "", "prty": "High",
+            "srvc": [{"rt": "61C", "stpid": ""}, {"rt": "61D", "stpid": ""}, {"rt": "61C"}]},
+            {"nm": "Stop moved", "sbj": "Stop moved", "srvc": [{"rt": "", "stpid": "4407"}]},
+            {"nm": "Mixed", "srvc": [{"rt": "67", "stpid": ""}, {"rt": "", "stpid": "4407"}]}]}}"#;
+
+        let alerts = parse_bulletins(raw, &ApiKey::new(String::new())).unwrap();
+        let alert = &alerts[0];
+        assert_eq!(alert.id, "bulletin:Smithfield closure");
+        assert_eq!(alert.title, "Smithfield St closed");
+        assert_eq!(alert.detail, "Buses use Wood St & Liberty");
+        let route = |rt: &str| AlertScope {
+            route: Some(rt.to_string()),
+            stop: None,
+        };
+        assert_eq!(alert.applies_to, [route("61C"), route("61D")]);
+        assert_eq!(
+            alerts[1].applies_to,
+            [AlertScope {
+                route: None,
+                stop: Some("4407".to_string()),
+            }],
+            "every route at the stop"
+        );
+        assert_eq!(
+            alerts[2].applies_to,
+            [
+                AlertScope {
+                    route: None,
+                    stop: Some("4407".to_string()),
+                },
+                route("67"),
+            ],
+            "kept as pairs, not widened to everywhere"
+        );
+    }
+
+    #[test]
+    fn keeps_the_latest_version_of_active_detours() {
+        let raw = r#"{"bustime-response": {"dtrs": [
+            {"id": "7", "ver": 1, "st": 1, "desc": "Old route", "rtdirs": [{"rt": "67", "dir": "INBOUND"}],
+                "startdt": "20261001 05:00", "enddt": "20261231 23:59"},
+            {"id": "7", "ver": "2", "st": "1", "desc": "Forbes Ave paving", "rtdirs": [{"rt": "67", "dir": "INBOUND"}],
+                "startdt": "20261001 05:00", "enddt": "20261231 23:59"},
+            {"id": "9", "ver": 3, "st": 0, "desc": "Canceled", "rtdirs": [{"rt": "67", "dir": "OUTBOUND"}],
+                "startdt": "20261001 05:00", "enddt": "20261231 23:59:00"}]}}"#;
+
+        let alerts = parse_detours(raw, &ApiKey::new(String::new())).unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].id, "detour:7");
+        assert_eq!(alerts[0].title, "Forbes Ave paving");
+        assert_eq!(
+            alerts[0].ends_at.unwrap().to_rfc3339(),
+            "2026-12-31T23:59:00-05:00"
+        );
+    }
+
+    #[test]
+    fn parses_routes_without_empty_fields() {
+        let raw = r##"{"bustime-response": {"routes": [
+            {"rt": "61C", "rtnm": "McKeesport - Homestead", "rtclr": "#cc3399", "rtdd": "61C"},
+            {"rt": "RED", "rtnm": "Red Line", "rtclr": "", "rtdd": "RED"}]}}"##;
+
+        let routes = parse_routes(raw, &ApiKey::new(String::new()), Mode::Rail).unwrap();
+        assert_eq!(routes[0].name.as_deref(), Some("McKeesport - Homestead"));
+        assert_eq!(routes[0].color.as_deref(), Some("#cc3399"));
+        assert_eq!(routes[1].color, None);
+        assert!(routes.iter().all(|r| r.mode == Mode::Rail));
+    }
+}
@@ -0,0 +1,227 @@
+// circuit breaker for PRT
+// after enough consecutive failed requests, stop calling PRT for a cooldown period,
+// then let a single trial request through to decide whether to resume
+
+use serde::Serialize;
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+// the cooldown doubles on every failed trial, up to this
+const MAX_COOLDOWN: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    Closed,
+    Open,
+    // cooldown is over and a trial request is allowed
+    HalfOpen,
+}
+
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    base_cooldown: Duration,
+    inner: Mutex<Inner>,
+}
+
+struct Inner {
+    consecutive_failures: u32,
+    // set while open
+    open_until: Option<Instant>,
+    // once the cooldown is over, whether the one trial request has been let through
+    trial_in_flight: bool,
+    cooldown: Duration,
+    times_opened: u64,
+}
+
+// what clients and /metrics get to see
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct CircuitStatus {
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    // seconds until a trial request is allowed, while open
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_in_seconds: Option<u64>,
+    #[serde(skip)]
+    pub times_opened: u64,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        CircuitBreaker {
+            failure_threshold: failure_threshold.max(1),
+            base_cooldown: cooldown,
+            inner: Mutex::new(Inner {
+                consecutive_failures: 0,
+                open_until: None,
+                trial_in_flight: false,
+                cooldown,
+                times_opened: 0,
+            }),
+        }
+    }
+
+    // false while open; callers should not contact PRT at all
+    // once the cooldown is over only one caller gets true, and must record how it went
+    // (or cancel_trial if it never asked)
+    pub fn allow_request(&self) -> bool {
+        self.allow_request_at(Instant::now())
+    }
+
+    fn allow_request_at(&self, now: Instant) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.open_until {
+            Some(until) if now >= until && !inner.trial_in_flight => {
+                inner.trial_in_flight = true;
+                true
+            }
+            Some(_) => false,
+            None => true,
+        }
+    }
+
+    // lets another caller make the trial request, when the one allowed didn't send it
+    pub fn cancel_trial(&self) {
+        self.inner.lock().unwrap().trial_in_flight = false;
+    }
+
+    pub fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.open_until.is_some() {
+            println!("Circuit closed, PRT is answering again");
+        }
+        inner.consecutive_failures = 0;
+        inner.open_until = None;
+        inner.trial_in_flight = false;
+        inner.cooldown = self.base_cooldown;
+    }
+
+    pub fn record_failure(&self) {
+        self.record_failure_at(Instant::now());
+    }
+
+    fn record_failure_at(&self, now: Instant) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures += 1;
+
+        // requests sent before the circuit opened can fail while it's open; only the trial's
+        // failure backs off further
+        let failed_trial = inner.trial_in_flight;
+        if failed_trial {
+            inner.trial_in_flight = false;
+            inner.cooldown = (inner.cooldown * 2).min(MAX_COOLDOWN);
+        }
+
+        let opening =
+            inner.open_until.is_none() && inner.consecutive_failures >= self.failure_threshold;
+        if failed_trial || opening {
+            inner.open_until = Some(now + inner.cooldown);
+            inner.times_opened += 1;
+            println!(
+                "Circuit open after {} consecutive failures, pausing PRT requests for {}s",
+                inner.consecutive_failures,
+                inner.cooldown.as_secs()
+            );
+        }
+    }
+
+    pub fn status(&self) -> CircuitStatus {
+        self.status_at(Instant::now())
+    }
+
+    fn status_at(&self, now: Instant) -> CircuitStatus {
+        let inner = self.inner.lock().unwrap();
+
+        let (state, retry_in_seconds) = match inner.open_until {
+            None => (CircuitState::Closed, None),
+            Some(until) if now >= until => (CircuitState::HalfOpen, None),
+            Some(until) => (CircuitState::Open, Some((until - now).as_secs() + 1)),
+        };
+
+        CircuitStatus {
+            state,
+            consecutive_failures: inner.consecutive_failures,
+            retry_in_seconds,
+            times_opened: inner.times_opened,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COOLDOWN: Duration = Duration::from_secs(30);
+
+    #[test]
+    fn opens_after_consecutive_failures_and_closes_on_success() {
+        let breaker = CircuitBreaker::new(3, COOLDOWN);
+        let start = Instant::now();
+
+        breaker.record_failure_at(start);
+        breaker.record_failure_at(start);
+        breaker.record_success();
+        breaker.record_failure_at(start);
+        breaker.record_failure_at(start);
+        assert_eq!(breaker.status_at(start).state, CircuitState::Closed);
+        assert!(breaker.allow_request_at(start));
+
+        breaker.record_failure_at(start);
+        let status = breaker.status_at(start);
+        assert_eq!(status.state, CircuitState::Open);
+        assert_eq!(status.retry_in_seconds, Some(31));
+        assert_eq!(status.times_opened, 1);
+        assert!(!breaker.allow_request_at(start + COOLDOWN / 2));
+
+        // half-open: one trial, however many ask
+        let later = start + COOLDOWN;
+        assert_eq!(breaker.status_at(later).state, CircuitState::HalfOpen);
+        assert!(breaker.allow_request_at(later));
+        assert!(!breaker.allow_request_at(later));
+
+        breaker.record_success();
+        assert_eq!(breaker.status_at(later).state, CircuitState::Closed);
+        assert_eq!(breaker.status_at(later).consecutive_failures, 0);
+        assert!(breaker.allow_request_at(later));
+        assert!(breaker.allow_request_at(later));
+    }
+
+    #[test]
+    fn failed_trials_double_the_cooldown_once_each() {
+        let breaker = CircuitBreaker::new(1, COOLDOWN);
+        let start = Instant::now();
+        breaker.record_failure_at(start);
+
+        let trial = start + COOLDOWN;
+        assert!(breaker.allow_request_at(trial));
+        breaker.record_failure_at(trial);
+        // a request sent before the circuit opened, failing late
+        breaker.record_failure_at(trial);
+        let status = breaker.status_at(trial);
+        assert_eq!(status.state, CircuitState::Open);
+        assert_eq!(status.retry_in_seconds, Some(61), "doubled once, not twice");
+        assert_eq!(status.times_opened, 2);
+
+        // a trial that was never sent lets the next caller try instead
+        let trial = trial + 2 * COOLDOWN;
+        assert!(breaker.allow_request_at(trial));
+        breaker.cancel_trial();
+        assert!(breaker.allow_request_at(trial));
+        breaker.record_failure_at(trial);
+        assert_eq!(breaker.status_at(trial).retry_in_seconds, Some(121));
+
+        // capped
+        let mut at = trial;
+        for _ in 0..10 {
+            at += MAX_COOLDOWN;
+            assert!(breaker.allow_request_at(at));
+            breaker.record_failure_at(at);
+        }
+        assert_eq!(
+            breaker.status_at(at).retry_in_seconds,
+            Some(MAX_COOLDOWN.as_secs() + 1)
+        );
+    }
+}
@@ -0,0 +1,441 @@
+// sign configuration
+// one backend can drive several signs; each sign profile picks from the shared stop list
+// loaded once at startup from the TOML file named by SIGN_CONFIG (default: sign.toml),
+// or from a comma separated PRT_STOPS list if no config file exists
+// service hours are optional, see service.rs
+// so is the timetable that fills in for missing live data, see schedule.rs
+// and a file of route names and colors to use instead of PRT's, see routes.rs
+
+use crate::service::{ServiceHours, ServiceStatus};
+use crate::source::Mode;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{env, fs, path::Path};
+
+const DEFAULT_CONFIG_PATH: &str = "sign.toml";
+
+const DEFAULT_SIGN_ID: &str = "default";
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Config {
+    pub stops: Vec<StopConfig>,
+    // if no signs are configured, a "default" sign shows every stop
+    #[serde(default)]
+    pub signs: Vec<SignProfile>,
+    // without a schedule, service is assumed to run around the clock
+    #[serde(default)]
+    pub service: Option<ServiceHours>,
+    #[serde(default)]
+    pub source: SourceConfig,
+    #[serde(default)]
+    pub schedule: Option<ScheduleConfig>,
+    #[serde(default)]
+    pub routes: Option<RoutesConfig>,
+}
+
+// where predictions come from, see source.rs
+#[derive(Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum SourceConfig {
+    // PRT's BusTime API, using PRT_API_KEY
+    Bustime {
+        // BusTime's real-time feeds to ask, see bustime.rs; just the bus feed if empty
+        #[serde(default)]
+        feeds: Vec<FeedConfig>,
+    },
+    // a GTFS-realtime TripUpdates feed plus the agency's GTFS static zip, each a URL or a path
+    GtfsRt {
+        trip_updates: String,
+        gtfs_static: String,
+    },
+}
+
+impl Default for SourceConfig {
+    fn default() -> Self {
+        SourceConfig::Bustime { feeds: Vec::new() }
+    }
+}
+
+// one of BusTime's rtpidatafeeds ("Port Authority Bus", "Light Rail", ...) and where to ask it
+#[derive(Deserialize, Debug, Clone)]
+pub struct FeedConfig {
+    pub name: String,
+    #[serde(default)]
+    pub mode: Mode,
+    // the configured stops it serves (empty = all of them)
+    #[serde(default)]
+    pub stops: Vec<String>,
+}
+
+// scheduled departures for when live data is missing
+#[derive(Deserialize, Debug, Clone)]
+pub struct ScheduleConfig {
+    // the agency's GTFS static zip, a URL or a path
+    pub gtfs_static: String,
+}
+
+// local route names and colors, taking precedence over the source's
+#[derive(Deserialize, Debug, Clone)]
+pub struct RoutesConfig {
+    // a TOML file with a table per route, see routes.example.toml
+    pub overrides: String,
+}
+
+// display metadata for one stop, passed through to the frontend as-is
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct StopConfig {
+    pub id: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub walk_time: Option<String>,
+    #[serde(default)]
+    pub side: Option<String>,
+}
+
+// one physical sign: which of the configured stops it shows and how
+#[derive(Deserialize, Debug, Clone)]
+pub struct SignProfile {
+    pub id: String,
+    pub stops: Vec<String>,
+    // only show these routes (empty = all routes)
+    #[serde(default)]
+    pub routes: Vec<String>,
+    // never show these routes
+    #[serde(default)]
+    pub exclude_routes: Vec<String>,
+    #[serde(default)]
+    pub order: RouteOrder,
+}
+
+// ordering of route groups within each stop
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RouteOrder {
+    // soonest arrival first
+    #[default]
+    Arrival,
+    // alphabetical by route, then destination
+    Route,
+    // in the order given by the sign's `routes` list
+    Listed,
+}
+
+impl SignProfile {
+    pub fn shows_route(&self, route: &str) -> bool {
+        (self.routes.is_empty() || self.routes.iter().any(|r| r == route))
+            && !self.exclude_routes.iter().any(|r| r == route)
+    }
+}
+
+impl Config {
+    pub fn load() -> Result<Self, String> {
+        let explicit_path = env::var("SIGN_CONFIG").ok();
+        let path = explicit_path.as_deref().unwrap_or(DEFAULT_CONFIG_PATH);
+
+        // a missing file is only an error if the path was asked for explicitly
+        if explicit_path.is_some() || Path::new(path).exists() {
+            return Self::from_file(path);
+        }
+
+        if let Ok(stops) = env::var("PRT_STOPS") {
+            return Self::from_stop_list(&stops);
+        }
+
+        Ok(Self::default())
+    }
+
+    fn from_file(path: &str) -> Result<Self, String> {
+        let text =
+            fs::read_to_string(path).map_err(|e| format!("could not read {}: {}", path, e))?;
+        let mut config: Config =
+            toml::from_str(&text).map_err(|e| format!("could not parse {}: {}", path, e))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn from_stop_list(list: &str) -> Result<Self, String> {
+        let stops = list
+            .split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(|id| StopConfig {
+                id: id.to_string(),
+                label: None,
+                walk_time: None,
+                side: None,
+            })
+            .collect();
+
+        let mut config = Config {
+            stops,
+            signs: Vec::new(),
+            service: None,
+            source: SourceConfig::default(),
+            schedule: None,
+            routes: None,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&mut self) -> Result<(), String> {
+        if self.stops.is_empty() {
+            return Err("at least one stop must be configured".to_string());
+        }
+        for (i, stop) in self.stops.iter().enumerate() {
+            if stop.id.is_empty() {
+                return Err(format!("stop #{} has an empty id", i + 1));
+            }
+            if self.stops[..i].iter().any(|s| s.id == stop.id) {
+                return Err(format!("stop {} is configured more than once", stop.id));
+            }
+        }
+
+        if self.signs.is_empty() {
+            self.signs.push(SignProfile {
+                id: DEFAULT_SIGN_ID.to_string(),
+                stops: self.stops.iter().map(|s| s.id.clone()).collect(),
+                routes: Vec::new(),
+                exclude_routes: Vec::new(),
+                order: RouteOrder::Arrival,
+            });
+        }
+
+        for (i, sign) in self.signs.iter().enumerate() {
+            if sign.id.is_empty() {
+                return Err(format!("sign #{} has an empty id", i + 1));
+            }
+            if self.signs[..i].iter().any(|s| s.id == sign.id) {
+                return Err(format!("sign {} is configured more than once", sign.id));
+            }
+            if let Some(stop) = sign.stops.iter().find(|id| self.stop(id).is_none()) {
+                return Err(format!("sign {} uses unknown stop {}", sign.id, stop));
+            }
+            if sign.order == RouteOrder::Listed && sign.routes.is_empty() {
+                return Err(format!(
+                    "sign {} orders by listed routes but lists none",
+                    sign.id
+                ));
+            }
+        }
+
+        if let SourceConfig::Bustime { feeds } = &self.source {
+            for (i, feed) in feeds.iter().enumerate() {
+                if feed.name.is_empty() {
+                    return Err(format!("feed #{} has an empty name", i + 1));
+                }
+                if feeds[..i].iter().any(|f| f.name == feed.name) {
+                    return Err(format!("feed {} is configured more than once", feed.name));
+                }
+                if let Some(stop) = feed.stops.iter().find(|id| self.stop(id).is_none()) {
+                    return Err(format!("feed {} uses unknown stop {}", feed.name, stop));
+                }
+            }
+            let unserved = self.stops.iter().find(|stop| {
+                !feeds.is_empty()
+                    && !feeds
+                        .iter()
+                        .any(|f| f.stops.is_empty() || f.stops.contains(&stop.id))
+            });
+            if let Some(stop) = unserved {
+                return Err(format!("stop {} is in none of the feeds", stop.id));
+            }
+        }
+
+        if let Some(service) = &self.service {
+            service.validate()?;
+        }
+        Ok(())
+    }
+
+    pub fn service_status(&self, now: DateTime<Utc>) -> ServiceStatus {
+        match &self.service {
+            Some(service) => service.status(now),
+            None => ServiceStatus::running(),
+        }
+    }
+
+    // true while outside service hours with off-hours polling turned off
+    pub fn polling_suspended(&self, now: DateTime<Utc>) -> bool {
+        self.service
+            .as_ref()
+            .is_some_and(|s| s.off_hours_poll().is_none() && !s.status(now).in_service)
+    }
+
+    // the GTFS static feed scheduled departures come from, if any; a GTFS-rt source's feed
+    // doubles as the timetable unless [schedule] names another
+    pub fn timetable(&self) -> Option<&str> {
+        match (&self.schedule, &self.source) {
+            (Some(schedule), _) => Some(&schedule.gtfs_static),
+            (None, SourceConfig::GtfsRt { gtfs_static, .. }) => Some(gtfs_static),
+            (None, SourceConfig::Bustime { .. }) => None,
+        }
+    }
+
+    pub fn stop(&self, id: &str) -> Option<&StopConfig> {
+        self.stops.iter().find(|s| s.id == id)
+    }
+
+    pub fn sign(&self, id: &str) -> Option<&SignProfile> {
+        self.signs.iter().find(|s| s.id == id)
+    }
+
+    // comma separated stop IDs, for logging
+    // every stop is fetched once, no matter how many signs show it
+    pub fn stop_ids(&self) -> String {
+        self.stops
+            .iter()
+            .map(|s| s.id.as_str())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+// the original Forbes and Morewood sign
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            stops: vec![
+                StopConfig {
+                    id: "7117".to_string(),
+                    label: Some("UC Side".to_string()),
+                    walk_time: Some("1-2 minutes".to_string()),
+                    side: Some("north".to_string()),
+                },
+                StopConfig {
+                    id: "4407".to_string(),
+                    label: Some("Tepper Side".to_string()),
+                    walk_time: Some("3-5 minutes".to_string()),
+                    side: Some("south".to_string()),
+                },
+            ],
+            signs: vec![SignProfile {
+                id: DEFAULT_SIGN_ID.to_string(),
+                stops: vec!["7117".to_string(), "4407".to_string()],
+                routes: Vec::new(),
+                exclude_routes: Vec::new(),
+                order: RouteOrder::Arrival,
+            }],
+            service: None,
+            source: SourceConfig::default(),
+            schedule: None,
+            routes: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<Config, String> {
+        let mut config: Config = toml::from_str(text).map_err(|e| e.to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    const STOPS: &str = r#"
+        [[stops]]
+        id = "7117"
+
+        [[stops]]
+        id = "4407"
+    "#;
+
+    #[test]
+    fn adds_a_default_sign_with_every_stop() {
+        let config = parse(STOPS).unwrap();
+        assert_eq!(config.signs.len(), 1);
+        assert_eq!(config.signs[0].id, DEFAULT_SIGN_ID);
+        assert_eq!(config.signs[0].stops, ["7117", "4407"]);
+
+        let config = Config::from_stop_list(" 7117, ,4407 ").unwrap();
+        assert_eq!(config.stop_ids(), "7117,4407");
+    }
+
+    #[test]
+    fn rejects_an_empty_stop_list() {
+        assert_eq!(
+            parse("stops = []").unwrap_err(),
+            "at least one stop must be configured"
+        );
+        assert_eq!(
+            Config::from_stop_list(" , ").unwrap_err(),
+            "at least one stop must be configured"
+        );
+    }
+
+    #[test]
+    fn rejects_duplicate_and_empty_ids() {
+        let duplicate_stop = format!("{}\n[[stops]]\nid = \"7117\"", STOPS);
+        assert_eq!(
+            parse(&duplicate_stop).unwrap_err(),
+            "stop 7117 is configured more than once"
+        );
+        assert_eq!(
+            parse("[[stops]]\nid = \"\"").unwrap_err(),
+            "stop #1 has an empty id"
+        );
+
+        let duplicate_sign = format!(
+            "{}\n[[signs]]\nid = \"lobby\"\nstops = [\"7117\"]\n[[signs]]\nid = \"lobby\"\nstops = [\"4407\"]",
+            STOPS
+        );
+        assert_eq!(
+            parse(&duplicate_sign).unwrap_err(),
+            "sign lobby is configured more than once"
+        );
+
+        let duplicate_feed = format!(
+            "{}\n[source]\ntype = \"bustime\"\n[[source.feeds]]\nname = \"Light Rail\"\n[[source.feeds]]\nname = \"Light Rail\"",
+            STOPS
+        );
+        assert_eq!(
+            parse(&duplicate_feed).unwrap_err(),
+            "feed Light Rail is configured more than once"
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_stop_references() {
+        let sign = format!(
+            "{}\n[[signs]]\nid = \"lobby\"\nstops = [\"7117\", \"9999\"]",
+            STOPS
+        );
+        assert_eq!(
+            parse(&sign).unwrap_err(),
+            "sign lobby uses unknown stop 9999"
+        );
+
+        let feed = format!(
+            "{}\n[source]\ntype = \"bustime\"\n[[source.feeds]]\nname = \"Light Rail\"\nstops = [\"9999\"]",
+            STOPS
+        );
+        assert_eq!(
+            parse(&feed).unwrap_err(),
+            "feed Light Rail uses unknown stop 9999"
+        );
+
+        let unserved = format!(
+            "{}\n[source]\ntype = \"bustime\"\n[[source.feeds]]\nname = \"Light Rail\"\nstops = [\"4407\"]",
+            STOPS
+        );
+        assert_eq!(
+            parse(&unserved).unwrap_err(),
+            "stop 7117 is in none of the feeds"
+        );
+    }
+
+    #[test]
+    fn rejects_listed_order_without_routes() {
+        let sign = format!(
+            "{}\n[[signs]]\nid = \"lobby\"\nstops = [\"7117\"]\norder = \"listed\"",
+            STOPS
+        );
+        assert_eq!(
+            parse(&sign).unwrap_err(),
+            "sign lobby orders by listed routes but lists none"
+        );
+    }
+}
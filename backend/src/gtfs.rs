@@ -0,0 +1,542 @@
+// GTFS static feed (a zip of CSV tables), read once at startup
+// gives realtime feeds their route names and trip destinations (see gtfs_rt.rs), and holds
+// the timetable scheduled departures come from (see schedule.rs)
+
+use crate::config::StopConfig;
+use crate::source::Mode;
+use chrono::{Datelike, NaiveDate};
+use std::collections::{HashMap, HashSet};
+use std::io::Cursor;
+
+const DATE_FORMAT: &str = "%Y%m%d";
+
+pub struct GtfsStatic {
+    pub routes: HashMap<String, Route>,
+    // only the trips that call at the configured stops, like the tables below; the full
+    // tables are huge, stop_times.txt especially, so they are filtered as they are read
+    pub trips: HashMap<String, Trip>,
+    // stop_id -> stop_name
+    pub stops: HashMap<String, String>,
+    // stop_id -> departures
+    pub stop_times: HashMap<String, Vec<StopTime>>,
+    // service_id -> the days of the week it runs and the dates it runs between
+    services: HashMap<String, Service>,
+    // calendar_dates.txt: date -> service_id -> whether it was added (or removed) that day
+    exceptions: HashMap<NaiveDate, HashMap<String, bool>>,
+}
+
+pub struct Route {
+    // what riders know the route by ("61C")
+    pub name: String,
+    pub mode: Mode,
+}
+
+pub struct Trip {
+    pub route_id: String,
+    pub headsign: String,
+    pub service_id: String,
+}
+
+pub struct StopTime {
+    pub trip_id: String,
+    // seconds after "noon minus 12h" of the service day, past 24h for trips after midnight
+    pub departure: u32,
+}
+
+struct Service {
+    // Monday first
+    days: [bool; 7],
+    start: NaiveDate,
+    end: NaiveDate,
+}
+
+impl GtfsStatic {
+    // `location` is a URL or a file path; timetables are kept for `stops` only
+    pub async fn load(
+        client: &reqwest::Client,
+        location: &str,
+        stops: &[StopConfig],
+    ) -> Result<Self, String> {
+        let archive = read_location(client, location).await?;
+        Self::from_zip(&archive, stops).map_err(|e| format!("{}: {}", location, e))
+    }
+
+    // the route's name, or its ID if the feed doesn't list it
+    pub fn route_name<'a>(&'a self, route_id: &'a str) -> &'a str {
+        self.routes
+            .get(route_id)
+            .map_or(route_id, |r| r.name.as_str())
+    }
+
+    pub fn route_mode(&self, route_id: &str) -> Mode {
+        self.routes
+            .get(route_id)
+            .map(|r| r.mode)
+            .unwrap_or_default()
+    }
+
+    // whether `service_id` runs on the service day `date`
+    pub fn runs_on(&self, service_id: &str, date: NaiveDate) -> bool {
+        if let Some(&added) = self
+            .exceptions
+            .get(&date)
+            .and_then(|services| services.get(service_id))
+        {
+            return added;
+        }
+        self.services.get(service_id).is_some_and(|s| {
+            s.start <= date
+                && date <= s.end
+                && s.days[date.weekday().num_days_from_monday() as usize]
+        })
+    }
+
+    fn from_zip(archive: &[u8], timetable_stops: &[StopConfig]) -> Result<Self, String> {
+        let configured = |stop_id: &str| timetable_stops.iter().any(|s| s.id == stop_id);
+
+        let mut routes = HashMap::new();
+        each_row(archive, "routes.txt", |row| {
+            // routes without a short name go by their long name
+            let name = match row.get("route_short_name") {
+                "" => row.get("route_long_name"),
+                short => short,
+            };
+            routes.insert(
+                row.get("route_id").to_string(),
+                Route {
+                    name: name.to_string(),
+                    mode: mode(row.get("route_type")),
+                },
+            );
+        })?;
+
+        let mut stops = HashMap::new();
+        each_row(archive, "stops.txt", |row| {
+            if configured(row.get("stop_id")) {
+                stops.insert(
+                    row.get("stop_id").to_string(),
+                    row.get("stop_name").to_string(),
+                );
+            }
+        })?;
+
+        // realtime feeds look up every trip at the stops, even ones that only let riders off
+        let mut stop_times: HashMap<String, Vec<StopTime>> = HashMap::new();
+        let mut trip_ids = HashSet::new();
+        each_row(archive, "stop_times.txt", |row| {
+            let stop_id = row.get("stop_id");
+            if !configured(stop_id) {
+                return;
+            }
+            trip_ids.insert(row.get("trip_id").to_string());
+            // pickup_type 1: passengers can only get off, so nobody waits for it here
+            if row.get("pickup_type") == "1" {
+                return;
+            }
+            // stops between timepoints may have no times at all
+            let time = match row.get("departure_time") {
+                "" => row.get("arrival_time"),
+                time => time,
+            };
+            let Some(departure) = parse_time(time) else {
+                return;
+            };
+            stop_times
+                .entry(stop_id.to_string())
+                .or_default()
+                .push(StopTime {
+                    trip_id: row.get("trip_id").to_string(),
+                    departure,
+                });
+        })?;
+
+        let mut trips = HashMap::new();
+        each_row(archive, "trips.txt", |row| {
+            if trip_ids.contains(row.get("trip_id")) {
+                trips.insert(
+                    row.get("trip_id").to_string(),
+                    Trip {
+                        route_id: row.get("route_id").to_string(),
+                        headsign: row.get("trip_headsign").to_string(),
+                        service_id: row.get("service_id").to_string(),
+                    },
+                );
+            }
+        })?;
+
+        // a feed may describe its service with either file alone
+        let mut services = HashMap::new();
+        each_optional_row(archive, "calendar.txt", |row| {
+            let day = |column| row.get(column) == "1";
+            let (Some(start), Some(end)) = (
+                parse_date(row.get("start_date")),
+                parse_date(row.get("end_date")),
+            ) else {
+                return;
+            };
+            services.insert(
+                row.get("service_id").to_string(),
+                Service {
+                    days: [
+                        day("monday"),
+                        day("tuesday"),
+                        day("wednesday"),
+                        day("thursday"),
+                        day("friday"),
+                        day("saturday"),
+                        day("sunday"),
+                    ],
+                    start,
+                    end,
+                },
+            );
+        })?;
+
+        let mut exceptions: HashMap<NaiveDate, HashMap<String, bool>> = HashMap::new();
+        each_optional_row(archive, "calendar_dates.txt", |row| {
+            let Some(date) = parse_date(row.get("date")) else {
+                return;
+            };
+            // exception_type 1 adds service that day, 2 removes it
+            exceptions.entry(date).or_default().insert(
+                row.get("service_id").to_string(),
+                row.get("exception_type") == "1",
+            );
+        })?;
+
+        Ok(GtfsStatic {
+            routes,
+            trips,
+            stops,
+            stop_times,
+            services,
+            exceptions,
+        })
+    }
+}
+
+// route_type, including the extended types some feeds use
+fn mode(route_type: &str) -> Mode {
+    match route_type.trim().parse::<u32>() {
+        // tram or light rail, subway, rail
+        Ok(0..=2 | 12 | 100..=199 | 400..=499 | 900..=999) => Mode::Rail,
+        // funicular
+        Ok(7 | 1400..=1499) => Mode::Incline,
+        _ => Mode::Bus,
+    }
+}
+
+fn parse_date(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text.trim(), DATE_FORMAT).ok()
+}
+
+// "H:MM:SS" or "HH:MM:SS", where hours can run past 24 for the service day's late trips
+fn parse_time(text: &str) -> Option<u32> {
+    let mut parts = text.trim().split(':').map(|part| part.parse::<u32>().ok());
+    let (Some(Some(h)), Some(Some(m)), Some(Some(s)), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+    (m < 60 && s < 60).then_some(h * 3600 + m * 60 + s)
+}
+
+// the bytes at a URL or in a file
+pub async fn read_location(client: &reqwest::Client, location: &str) -> Result<Vec<u8>, String> {
+    if location.starts_with("http://") || location.starts_with("https://") {
+        let resp = client
+            .get(location)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| fetch_error(location, e))?;
+        let bytes = resp.bytes().await.map_err(|e| fetch_error(location, e))?;
+        Ok(bytes.to_vec())
+    } else {
+        tokio::fs::read(location)
+            .await
+            .map_err(|e| format!("could not read {}: {}", location, e))
+    }
+}
+
+// reqwest's own message for a timeout doesn't say so
+fn fetch_error(location: &str, e: reqwest::Error) -> String {
+    if e.is_timeout() {
+        format!("timed out fetching {}", location)
+    } else {
+        format!("could not fetch {}: {}", location, e.without_url())
+    }
+}
+
+// one row of a CSV file of the feed; GTFS only promises which columns exist, not their order
+pub struct Row<'a> {
+    record: &'a csv::StringRecord,
+    columns: &'a HashMap<String, usize>,
+}
+
+impl Row<'_> {
+    // "" for a column the file doesn't have, same as an empty field
+    pub fn get(&self, column: &str) -> &str {
+        self.columns
+            .get(column)
+            .and_then(|&i| self.record.get(i))
+            .unwrap_or("")
+    }
+}
+
+// calls `f` with each row of the file called `name`, in any folder of the archive, as it is
+// decompressed; an error if there is no such file
+pub fn each_row(archive: &[u8], name: &str, f: impl FnMut(Row)) -> Result<(), String> {
+    if read_rows(archive, name, f)? {
+        Ok(())
+    } else {
+        Err(format!("missing {}", name))
+    }
+}
+
+// the same, with no rows for a file the feed doesn't have
+pub fn each_optional_row(archive: &[u8], name: &str, f: impl FnMut(Row)) -> Result<(), String> {
+    read_rows(archive, name, f).map(|_| ())
+}
+
+// false if the archive has no file called `name`
+fn read_rows(archive: &[u8], name: &str, mut f: impl FnMut(Row)) -> Result<bool, String> {
+    let mut archive =
+        zip::ZipArchive::new(Cursor::new(archive)).map_err(|e| format!("bad zip file: {}", e))?;
+    let Some(path) = archive
+        .file_names()
+        .find(|path| path.rsplit('/').next() == Some(name))
+        .map(str::to_string)
+    else {
+        return Ok(false);
+    };
+    let file = archive
+        .by_name(&path)
+        .map_err(|e| format!("could not read {}: {}", path, e))?;
+
+    // rows may leave off trailing empty fields; csv skips a leading byte order mark itself
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::Headers)
+        .from_reader(file);
+    let columns = reader
+        .headers()
+        .map_err(|e| format!("{}: {}", name, e))?
+        .iter()
+        .enumerate()
+        .map(|(i, column)| (column.to_string(), i))
+        .collect();
+    for record in reader.records() {
+        let record = record.map_err(|e| format!("{}: {}", name, e))?;
+        f(Row {
+            record: &record,
+            columns: &columns,
+        });
+    }
+    Ok(true)
+}
+
+// a feed with one stop and a service that runs every day, for testing the timetable
+#[cfg(test)]
+pub mod testing {
+    use super::*;
+
+    // `trips` are (trip_id, route, headsign, departure time at `stop`)
+    pub fn timetable(stop: &str, trips: &[(&str, &str, &str, &str)]) -> GtfsStatic {
+        let mut feed = GtfsStatic {
+            routes: HashMap::new(),
+            trips: HashMap::new(),
+            stops: HashMap::from([(stop.to_string(), stop.to_string())]),
+            stop_times: HashMap::new(),
+            services: HashMap::from([(
+                "daily".to_string(),
+                Service {
+                    days: [true; 7],
+                    start: NaiveDate::MIN,
+                    end: NaiveDate::MAX,
+                },
+            )]),
+            exceptions: HashMap::new(),
+        };
+        for &(trip_id, route, headsign, time) in trips {
+            feed.routes.insert(
+                route.to_string(),
+                Route {
+                    name: route.to_string(),
+                    mode: Mode::Bus,
+                },
+            );
+            feed.trips.insert(
+                trip_id.to_string(),
+                Trip {
+                    route_id: route.to_string(),
+                    headsign: headsign.to_string(),
+                    service_id: "daily".to_string(),
+                },
+            );
+            feed.stop_times
+                .entry(stop.to_string())
+                .or_default()
+                .push(StopTime {
+                    trip_id: trip_id.to_string(),
+                    departure: parse_time(time).unwrap(),
+                });
+        }
+        feed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a deflated trips.txt in a folder (with a BOM, CRLFs and quoted fields) and a stored agency.txt
+    const ARCHIVE: &[u8] = &[
+        0x50, 0x4b, 0x03, 0x04, 0x14, 0x00, 0x00, 0x00, 0x08, 0x00, 0x08, 0x45, 0x52, 0x5d, 0x5a,
+        0x27, 0xec, 0x49, 0x41, 0x00, 0x00, 0x00, 0x98, 0x00, 0x00, 0x00, 0x0e, 0x00, 0x00, 0x00,
+        0x66, 0x65, 0x65, 0x64, 0x2f, 0x74, 0x72, 0x69, 0x70, 0x73, 0x2e, 0x74, 0x78, 0x74, 0x7b,
+        0xbf, 0x7b, 0x7f, 0x49, 0x51, 0x66, 0x41, 0x7c, 0x66, 0x8a, 0x0e, 0x98, 0xce, 0x48, 0x4d,
+        0x4c, 0x29, 0xce, 0x4c, 0xcf, 0xe3, 0xe5, 0x0a, 0x31, 0xd4, 0x51, 0x72, 0xc9, 0x2f, 0xcf,
+        0x2b, 0x01, 0x62, 0x1d, 0x85, 0xb2, 0xcc, 0x44, 0x05, 0x25, 0x25, 0xb7, 0xcc, 0xb4, 0x92,
+        0x0c, 0x25, 0x25, 0x25, 0xa0, 0xac, 0x91, 0x8e, 0x7f, 0x62, 0x76, 0x4e, 0x62, 0x5e, 0x0a,
+        0x6d, 0x54, 0x02, 0x00, 0x50, 0x4b, 0x03, 0x04, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x08,
+        0x45, 0x52, 0x5d, 0xc6, 0x9e, 0xa0, 0xb6, 0x0e, 0x00, 0x00, 0x00, 0x0e, 0x00, 0x00, 0x00,
+        0x0a, 0x00, 0x00, 0x00, 0x61, 0x67, 0x65, 0x6e, 0x63, 0x79, 0x2e, 0x74, 0x78, 0x74, 0x61,
+        0x67, 0x65, 0x6e, 0x63, 0x79, 0x5f, 0x69, 0x64, 0x0a, 0x50, 0x52, 0x54, 0x0a, 0x50, 0x4b,
+        0x01, 0x02, 0x14, 0x03, 0x14, 0x00, 0x00, 0x00, 0x08, 0x00, 0x08, 0x45, 0x52, 0x5d, 0x5a,
+        0x27, 0xec, 0x49, 0x41, 0x00, 0x00, 0x00, 0x98, 0x00, 0x00, 0x00, 0x0e, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x01, 0x00, 0x00, 0x00, 0x00, 0x66,
+        0x65, 0x65, 0x64, 0x2f, 0x74, 0x72, 0x69, 0x70, 0x73, 0x2e, 0x74, 0x78, 0x74, 0x50, 0x4b,
+        0x01, 0x02, 0x14, 0x03, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x08, 0x45, 0x52, 0x5d, 0xc6,
+        0x9e, 0xa0, 0xb6, 0x0e, 0x00, 0x00, 0x00, 0x0e, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x01, 0x6d, 0x00, 0x00, 0x00, 0x61,
+        0x67, 0x65, 0x6e, 0x63, 0x79, 0x2e, 0x74, 0x78, 0x74, 0x50, 0x4b, 0x05, 0x06, 0x00, 0x00,
+        0x00, 0x00, 0x02, 0x00, 0x02, 0x00, 0x74, 0x00, 0x00, 0x00, 0xa3, 0x00, 0x00, 0x00, 0x00,
+        0x00,
+    ];
+
+    #[test]
+    fn reads_quoted_csv_from_a_zip() {
+        let mut rows: Vec<(String, String, String)> = Vec::new();
+        each_row(ARCHIVE, "trips.txt", |row| {
+            rows.push((
+                row.get("trip_id").into(),
+                row.get("trip_headsign").into(),
+                row.get("route_id").into(),
+            ))
+        })
+        .unwrap();
+        assert_eq!(rows.len(), 6);
+        assert_eq!(
+            rows[0],
+            ("T1".into(), r#"Downtown, via "Fifth""#.into(), "".into())
+        );
+        assert_eq!(rows[1], ("T2".into(), "Oakland".into(), "".into()));
+
+        let mut agencies = Vec::new();
+        each_row(ARCHIVE, "agency.txt", |row| {
+            agencies.push(row.get("agency_id").to_string())
+        })
+        .unwrap();
+        assert_eq!(agencies, ["PRT"]);
+
+        assert!(each_row(ARCHIVE, "stops.txt", |_| {}).is_err());
+        let mut none = 0;
+        each_optional_row(ARCHIVE, "stops.txt", |_| none += 1).unwrap();
+        assert_eq!(none, 0);
+    }
+
+    #[test]
+    fn keeps_only_the_configured_stops_and_their_trips() {
+        let files = [
+            (
+                "routes.txt",
+                "route_id,route_short_name,route_type\n61C,61C,3\n71B,71B,3\n",
+            ),
+            (
+                "trips.txt",
+                "route_id,service_id,trip_id,trip_headsign\n\
+                 61C,daily,A,McKeesport\n71B,daily,B,Highland Park\n61C,daily,C,Downtown\n",
+            ),
+            (
+                "stops.txt",
+                "stop_id,stop_name\n7117,Forbes at Morewood\n9999,Elsewhere\n",
+            ),
+            (
+                "stop_times.txt",
+                "trip_id,arrival_time,departure_time,stop_id,pickup_type\n\
+                 A,08:00:00,08:01:00,7117,0\nB,08:05:00,08:05:00,9999,0\n\
+                 C,09:00:00,09:00:00,7117,1\n",
+            ),
+        ];
+        let mut archive = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in files {
+            archive
+                .start_file(name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            std::io::Write::write_all(&mut archive, contents.as_bytes()).unwrap();
+        }
+        let archive = archive.finish().unwrap().into_inner();
+
+        let stop = StopConfig {
+            id: "7117".to_string(),
+            label: None,
+            walk_time: None,
+            side: None,
+        };
+        let feed = GtfsStatic::from_zip(&archive, &[stop]).unwrap();
+        assert_eq!(feed.stops.keys().collect::<Vec<_>>(), ["7117"]);
+        let mut trips: Vec<&String> = feed.trips.keys().collect();
+        trips.sort();
+        assert_eq!(
+            trips,
+            ["A", "C"],
+            "C only drops off, but realtime may mention it"
+        );
+        let departures: Vec<(&str, u32)> = feed.stop_times["7117"]
+            .iter()
+            .map(|t| (t.trip_id.as_str(), t.departure))
+            .collect();
+        assert_eq!(departures, [("A", 8 * 3600 + 60)]);
+        assert_eq!(feed.routes.len(), 2);
+    }
+
+    #[test]
+    fn calendar_dates_override_the_weekly_calendar() {
+        let mut feed = testing::timetable("7117", &[]);
+        let date = |d| NaiveDate::from_ymd_opt(2026, 11, d).unwrap();
+        feed.services.insert(
+            "weekday".to_string(),
+            Service {
+                days: [true, true, true, true, true, false, false],
+                start: date(1),
+                end: date(30),
+            },
+        );
+        feed.exceptions.insert(
+            date(26),
+            HashMap::from([("weekday".to_string(), false), ("daily".to_string(), true)]),
+        );
+        feed.exceptions
+            .insert(date(28), HashMap::from([("weekday".to_string(), true)]));
+
+        assert!(feed.runs_on("weekday", date(25)), "Wednesday");
+        assert!(
+            !feed.runs_on("weekday", date(26)),
+            "removed for Thanksgiving"
+        );
+        assert!(!feed.runs_on("weekday", date(29)), "Sunday");
+        assert!(feed.runs_on("weekday", date(28)), "added on a Saturday");
+        assert!(!feed.runs_on("weekday", NaiveDate::from_ymd_opt(2026, 12, 1).unwrap()));
+        assert!(!feed.runs_on("unknown", date(25)));
+    }
+
+    #[test]
+    fn parses_times_past_midnight() {
+        assert_eq!(parse_time("5:07:00"), Some(5 * 3600 + 7 * 60));
+        assert_eq!(parse_time(" 25:30:15"), Some(25 * 3600 + 30 * 60 + 15));
+        assert_eq!(parse_time(""), None);
+        assert_eq!(parse_time("12:60:00"), None);
+        assert_eq!(parse_time("12:00"), None);
+    }
+}
@@ -0,0 +1,482 @@
+// GTFS-realtime TripUpdates, for agencies (or a future PRT) without BusTime
+// the feed is a protobuf FeedMessage, fetched over HTTP or read from a file on every poll;
+// it only has IDs, so route names and destinations come from the GTFS static feed (gtfs.rs)
+//
+// only the handful of fields the sign uses are declared, see
+// https://gtfs.org/realtime/reference/
+
+use crate::AppError;
+use crate::config::StopConfig;
+use crate::gtfs::GtfsStatic;
+use crate::local_time::TIMEZONE;
+use crate::source::{Arrival, ArrivalDetails, ArrivalKind, PredictionSource, SourceUpdate};
+use crate::upstream::{ApiKey, UpstreamFailure};
+use chrono::{DateTime, Utc};
+use futures_util::future::BoxFuture;
+use prost::Message;
+use std::sync::Arc;
+
+pub struct GtfsRt {
+    client: reqwest::Client,
+    // URL or file path of the TripUpdates feed
+    trip_updates: String,
+    gtfs: Arc<GtfsStatic>,
+}
+
+impl GtfsRt {
+    pub fn new(
+        trip_updates: String,
+        gtfs: Arc<GtfsStatic>,
+        // with the upstream timeouts
+        client: reqwest::Client,
+        stops: &[StopConfig],
+    ) -> Self {
+        for stop in stops.iter().filter(|s| !gtfs.stops.contains_key(&s.id)) {
+            println!(
+                "Stop {} is not in the GTFS feed, it will never have arrivals",
+                stop.id
+            );
+        }
+
+        GtfsRt {
+            client,
+            trip_updates,
+            gtfs,
+        }
+    }
+
+    async fn fetch_all(&self, stops: &[StopConfig]) -> Result<SourceUpdate, AppError> {
+        println!("Fetching from GTFS-rt feed");
+        let bytes = self.read_feed().await?;
+        let feed = FeedMessage::parse(&bytes).map_err(AppError::ParseError)?;
+
+        let predicted_at = feed
+            .timestamp()
+            .and_then(|t| DateTime::from_timestamp(t as i64, 0))
+            .unwrap_or_else(Utc::now);
+
+        let mut arrivals = Vec::new();
+        for update in feed.trip_updates() {
+            let trip = self.gtfs.trips.get(update.trip_id());
+            let route_id = match (update.route_id(), trip) {
+                (Some(route_id), _) => route_id,
+                (None, Some(trip)) => trip.route_id.as_str(),
+                (None, None) => continue,
+            };
+            let route = self.gtfs.route_name(route_id);
+            let mode = self.gtfs.route_mode(route_id);
+
+            for stop_time in &update.stop_time_update {
+                if !stops.iter().any(|s| s.id == stop_time.stop_id()) {
+                    continue;
+                }
+                match stop_time.schedule_relationship() {
+                    ScheduleRelationship::Scheduled | ScheduleRelationship::Unscheduled => {}
+                    // the trip won't stop here
+                    ScheduleRelationship::Skipped => continue,
+                    // the trip still stops here, but there's no time to show; it isn't a
+                    // cancellation, and the timetable fills the stop if nothing else is live
+                    ScheduleRelationship::NoData => continue,
+                }
+                let Some(arrival_at) = stop_time
+                    .time()
+                    .and_then(|t| DateTime::from_timestamp(t, 0))
+                    .filter(|at| *at >= predicted_at)
+                else {
+                    continue;
+                };
+
+                arrivals.push(Arrival {
+                    stop: stop_time.stop_id().to_string(),
+                    route: route.to_string(),
+                    destination: trip.map(|t| t.headsign.clone()).unwrap_or_default(),
+                    bus_id: update.vehicle(),
+                    predicted_at: predicted_at.with_timezone(&TIMEZONE).fixed_offset(),
+                    arrival_at: arrival_at.with_timezone(&TIMEZONE).fixed_offset(),
+                    // occupancy is only in VehiclePositions, which isn't read
+                    capacity: String::new(),
+                    kind: ArrivalKind::Realtime,
+                    mode,
+                    details: ArrivalDetails {
+                        trip_id: Some(update.trip_id().to_string()).filter(|id| !id.is_empty()),
+                        ..ArrivalDetails::default()
+                    },
+                });
+            }
+        }
+
+        Ok(SourceUpdate {
+            arrivals,
+            ..SourceUpdate::default()
+        })
+    }
+
+    async fn read_feed(&self) -> Result<Vec<u8>, AppError> {
+        let location = &self.trip_updates;
+        if !(location.starts_with("http://") || location.starts_with("https://")) {
+            return tokio::fs::read(location).await.map_err(|e| {
+                println!("Could not read {}: {}", location, e);
+                AppError::UpstreamError(UpstreamFailure::Body)
+            });
+        }
+
+        // feeds that need a key take it in the URL, which from_reqwest keeps out of the logs
+        let no_key = ApiKey::new(String::new());
+        let resp = self
+            .client
+            .get(location)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| AppError::UpstreamError(UpstreamFailure::from_reqwest(e, &no_key)))?;
+        let bytes = resp
+            .bytes()
+            .await
+            .map_err(|e| AppError::UpstreamError(UpstreamFailure::from_reqwest(e, &no_key)))?;
+        Ok(bytes.to_vec())
+    }
+}
+
+impl PredictionSource for GtfsRt {
+    fn name(&self) -> &'static str {
+        "gtfs-rt"
+    }
+
+    fn fetch<'a>(
+        &'a self,
+        stops: &'a [StopConfig],
+    ) -> BoxFuture<'a, Result<SourceUpdate, AppError>> {
+        Box::pin(self.fetch_all(stops))
+    }
+}
+
+// --- INCOMING DATA (gtfs-realtime.proto, the parts the sign uses) ---
+// proto2 `required` fields are optional here, so a feed missing one still decodes
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct FeedMessage {
+    #[prost(message, optional, tag = "1")]
+    header: Option<FeedHeader>,
+    #[prost(message, repeated, tag = "2")]
+    entity: Vec<FeedEntity>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct FeedHeader {
+    // POSIX seconds
+    #[prost(uint64, optional, tag = "3")]
+    timestamp: Option<u64>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct FeedEntity {
+    #[prost(bool, optional, tag = "2")]
+    is_deleted: Option<bool>,
+    #[prost(message, optional, tag = "3")]
+    trip_update: Option<TripUpdate>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct TripUpdate {
+    #[prost(message, optional, tag = "1")]
+    trip: Option<TripDescriptor>,
+    #[prost(message, repeated, tag = "2")]
+    stop_time_update: Vec<StopTimeUpdate>,
+    #[prost(message, optional, tag = "3")]
+    vehicle: Option<VehicleDescriptor>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct TripDescriptor {
+    #[prost(string, optional, tag = "1")]
+    trip_id: Option<String>,
+    #[prost(string, optional, tag = "5")]
+    route_id: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct VehicleDescriptor {
+    #[prost(string, optional, tag = "1")]
+    id: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    label: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct StopTimeUpdate {
+    #[prost(message, optional, tag = "2")]
+    arrival: Option<StopTimeEvent>,
+    #[prost(message, optional, tag = "3")]
+    departure: Option<StopTimeEvent>,
+    #[prost(string, optional, tag = "4")]
+    stop_id: Option<String>,
+    #[prost(enumeration = "ScheduleRelationship", optional, tag = "5")]
+    schedule_relationship: Option<i32>,
+}
+
+// events with only a delay need the schedule, which isn't read here
+#[derive(Clone, PartialEq, prost::Message)]
+struct StopTimeEvent {
+    // POSIX seconds
+    #[prost(int64, optional, tag = "2")]
+    time: Option<i64>,
+}
+
+// StopTimeUpdate.ScheduleRelationship
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+enum ScheduleRelationship {
+    Scheduled = 0,
+    // the trip won't stop here
+    Skipped = 1,
+    // nothing is known about this stop, but the trip still stops here
+    NoData = 2,
+    // a trip run by frequency rather than a timetable
+    Unscheduled = 3,
+}
+
+impl FeedMessage {
+    fn parse(bytes: &[u8]) -> Result<Self, String> {
+        Self::decode(bytes).map_err(|e| format!("bad GTFS-rt feed: {}", e))
+    }
+
+    // POSIX seconds
+    fn timestamp(&self) -> Option<u64> {
+        self.header.as_ref().and_then(|h| h.timestamp)
+    }
+
+    fn trip_updates(&self) -> impl Iterator<Item = &TripUpdate> {
+        self.entity
+            .iter()
+            .filter(|entity| !entity.is_deleted())
+            .filter_map(|entity| entity.trip_update.as_ref())
+    }
+}
+
+impl TripUpdate {
+    fn trip_id(&self) -> &str {
+        self.trip.as_ref().map_or("", |trip| trip.trip_id())
+    }
+
+    fn route_id(&self) -> Option<&str> {
+        self.trip.as_ref().and_then(|trip| trip.route_id.as_deref())
+    }
+
+    // the vehicle's label, or its id if it has no label
+    fn vehicle(&self) -> String {
+        self.vehicle
+            .as_ref()
+            .and_then(|v| v.label.clone().or_else(|| v.id.clone()))
+            .unwrap_or_default()
+    }
+}
+
+impl StopTimeUpdate {
+    // StopTimeEvent.time of the arrival, or of the departure if there is no arrival time
+    fn time(&self) -> Option<i64> {
+        self.arrival
+            .as_ref()
+            .and_then(|e| e.time)
+            .or_else(|| self.departure.as_ref().and_then(|e| e.time))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::gtfs::{Route, testing::timetable};
+    use crate::source::{self, Mode};
+
+    fn stop_time(stop: &str, time: i64, relationship: ScheduleRelationship) -> StopTimeUpdate {
+        StopTimeUpdate {
+            arrival: Some(StopTimeEvent { time: Some(time) }),
+            departure: None,
+            stop_id: Some(stop.to_string()),
+            schedule_relationship: Some(relationship as i32),
+        }
+    }
+
+    #[test]
+    fn decodes_trip_updates() {
+        let update = TripUpdate {
+            trip: Some(TripDescriptor {
+                trip_id: Some("T1".to_string()),
+                route_id: Some("61C".to_string()),
+            }),
+            stop_time_update: vec![
+                stop_time("7117", 1_700_000_300, ScheduleRelationship::Scheduled),
+                stop_time("4407", 1_700_000_600, ScheduleRelationship::Skipped),
+                stop_time("8192", 1_700_000_900, ScheduleRelationship::NoData),
+            ],
+            vehicle: Some(VehicleDescriptor {
+                id: Some("3201".to_string()),
+                label: None,
+            }),
+        };
+        let entity = |deleted| FeedEntity {
+            is_deleted: Some(deleted),
+            trip_update: Some(update.clone()),
+        };
+        let feed = FeedMessage {
+            header: Some(FeedHeader {
+                timestamp: Some(1_700_000_000),
+            }),
+            // deleted entities are dropped
+            entity: vec![entity(false), entity(true)],
+        };
+
+        let feed = FeedMessage::parse(&feed.encode_to_vec()).unwrap();
+        assert_eq!(feed.timestamp(), Some(1_700_000_000));
+        let updates: Vec<&TripUpdate> = feed.trip_updates().collect();
+        assert_eq!(updates.len(), 1);
+
+        let update = updates[0];
+        assert_eq!(update.trip_id(), "T1");
+        assert_eq!(update.route_id(), Some("61C"));
+        assert_eq!(update.vehicle(), "3201", "falls back to the vehicle id");
+        let stop_times = &update.stop_time_update;
+        assert_eq!(stop_times[0].stop_id(), "7117");
+        assert_eq!(stop_times[0].time(), Some(1_700_000_300));
+        assert_eq!(
+            stop_times[0].schedule_relationship(),
+            ScheduleRelationship::Scheduled
+        );
+        assert_eq!(
+            stop_times[1].schedule_relationship(),
+            ScheduleRelationship::Skipped
+        );
+        assert_eq!(
+            stop_times[2].schedule_relationship(),
+            ScheduleRelationship::NoData
+        );
+    }
+
+    #[tokio::test]
+    async fn maps_updates_to_route_groups() {
+        let mut gtfs = timetable(
+            "7117",
+            &[
+                ("T1", "6100", "McKeesport", "08:00:00"),
+                ("T2", "RED", "South Hills Village", "08:10:00"),
+            ],
+        );
+        gtfs.routes.insert(
+            "6100".to_string(),
+            Route {
+                name: "61C".to_string(),
+                mode: Mode::Bus,
+            },
+        );
+        gtfs.routes.insert(
+            "RED".to_string(),
+            Route {
+                name: "Red Line".to_string(),
+                mode: Mode::Rail,
+            },
+        );
+
+        let now = 1_700_000_000;
+        let update = |trip: &str, route: Option<&str>, stop_times| TripUpdate {
+            trip: Some(TripDescriptor {
+                trip_id: Some(trip.to_string()),
+                route_id: route.map(str::to_string),
+            }),
+            stop_time_update: stop_times,
+            vehicle: Some(VehicleDescriptor {
+                id: Some(format!("bus-{}", trip)),
+                label: None,
+            }),
+        };
+        let feed = FeedMessage {
+            header: Some(FeedHeader {
+                timestamp: Some(now as u64),
+            }),
+            entity: [
+                // the route comes from the timetable's trip
+                update(
+                    "T1",
+                    None,
+                    vec![
+                        stop_time("7117", now + 300, ScheduleRelationship::Scheduled),
+                        stop_time("4407", now + 400, ScheduleRelationship::Skipped),
+                        stop_time("9999", now + 500, ScheduleRelationship::Scheduled),
+                    ],
+                ),
+                update(
+                    "T2",
+                    Some("RED"),
+                    vec![
+                        stop_time("7117", now - 60, ScheduleRelationship::Scheduled),
+                        stop_time("4407", now + 600, ScheduleRelationship::Scheduled),
+                    ],
+                ),
+                // neither the feed nor the timetable says which route
+                update(
+                    "T3",
+                    None,
+                    vec![stop_time(
+                        "7117",
+                        now + 120,
+                        ScheduleRelationship::Scheduled,
+                    )],
+                ),
+            ]
+            .into_iter()
+            .map(|update| FeedEntity {
+                is_deleted: None,
+                trip_update: Some(update),
+            })
+            .collect(),
+        };
+        let path = std::env::temp_dir().join(format!("trip-updates-{}.pb", std::process::id()));
+        std::fs::write(&path, feed.encode_to_vec()).unwrap();
+
+        let stops = Config::default().stops;
+        let source = GtfsRt::new(
+            path.to_string_lossy().into_owned(),
+            Arc::new(gtfs),
+            reqwest::Client::new(),
+            &stops,
+        );
+        let update = source.fetch_all(&stops).await;
+        std::fs::remove_file(&path).unwrap();
+        let data = source::group(update.unwrap().arrivals);
+
+        let mut stop_ids: Vec<&String> = data.keys().collect();
+        stop_ids.sort();
+        assert_eq!(stop_ids, ["4407", "7117"]);
+
+        let uc = &data["7117"];
+        assert_eq!(uc.len(), 1, "T2 has left, T3 has no route");
+        assert_eq!(uc[0].route, "61C");
+        assert_eq!(uc[0].destination, "McKeesport");
+        assert_eq!(uc[0].mode, Mode::Bus);
+        assert_eq!(uc[0].arrivals.len(), 1);
+        assert_eq!(uc[0].arrivals[0].seconds, 300);
+        assert_eq!(uc[0].arrivals[0].bus_id, "bus-T1");
+        assert_eq!(uc[0].arrivals[0].kind, ArrivalKind::Realtime);
+        assert_eq!(uc[0].arrivals[0].trip_id.as_deref(), Some("T1"));
+
+        let tepper = &data["4407"];
+        assert_eq!(tepper.len(), 1, "T1 skips it");
+        assert_eq!(tepper[0].route, "Red Line");
+        assert_eq!(tepper[0].destination, "South Hills Village");
+        assert_eq!(tepper[0].mode, Mode::Rail);
+        assert_eq!(tepper[0].arrivals[0].seconds, 600);
+    }
+
+    #[test]
+    fn rejects_malformed_feeds() {
+        // a header field claiming more bytes than there are
+        assert!(FeedMessage::parse(&[0x0a, 0x05, 0x01]).is_err());
+        // a length that would overflow when added to the position
+        assert!(
+            FeedMessage::parse(&[
+                0x0a, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01
+            ])
+            .is_err()
+        );
+        assert!(FeedMessage::parse(&[0x0a]).is_err());
+    }
+}
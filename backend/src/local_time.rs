@@ -0,0 +1,128 @@
+// PRT timestamps ("20260301 14:05:00") are Pittsburgh wall-clock times with no offset
+// they are turned into real instants here so countdowns stay right across DST changes
+
+use chrono::{DateTime, Duration, LocalResult, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::{America::New_York, Tz};
+
+pub const TIMEZONE: Tz = New_York;
+
+const FORMAT: &str = "%Y%m%d %H:%M:%S";
+
+// how far before the reference a time can be and still count as "after" it
+const SLACK_MINUTES: i64 = 5;
+
+// parses a PRT timestamp, using `reference` (a moment known to be at or just before it)
+// to pick the right instant when the wall time is ambiguous
+pub fn parse_after(text: &str, reference: DateTime<Utc>) -> Option<DateTime<Tz>> {
+    let naive = NaiveDateTime::parse_from_str(text, FORMAT).ok()?;
+
+    match TIMEZONE.from_local_datetime(&naive) {
+        LocalResult::Single(time) => Some(time),
+        // the hour that repeats when clocks fall back: take the first pass not before the reference
+        LocalResult::Ambiguous(early, late) => {
+            if early >= reference - Duration::minutes(SLACK_MINUTES) {
+                Some(early)
+            } else {
+                Some(late)
+            }
+        }
+        // the hour skipped when clocks spring forward; PRT shouldn't send these,
+        // but the wall time an hour later names the same instant
+        LocalResult::None => TIMEZONE
+            .from_local_datetime(&(naive + Duration::hours(1)))
+            .earliest(),
+    }
+}
+
+// a configured wall-clock time (service hours and the like): the first instant it names,
+// or the same time an hour later if clocks skip over it
+pub fn resolve(naive: NaiveDateTime) -> DateTime<Tz> {
+    match TIMEZONE.from_local_datetime(&naive) {
+        LocalResult::Single(time) | LocalResult::Ambiguous(time, _) => time,
+        LocalResult::None => TIMEZONE
+            .from_local_datetime(&(naive + Duration::hours(1)))
+            .earliest()
+            .unwrap_or_else(|| TIMEZONE.from_utc_datetime(&naive)),
+    }
+}
+
+// a PRT date and time to the minute or the second ("20261018 05:00", detours use these)
+pub fn parse_wall(text: &str) -> Option<DateTime<Tz>> {
+    NaiveDateTime::parse_from_str(text.trim(), FORMAT)
+        .or_else(|_| NaiveDateTime::parse_from_str(text.trim(), "%Y%m%d %H:%M"))
+        .ok()
+        .map(resolve)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn rfc3339(time: Option<DateTime<Tz>>) -> String {
+        time.unwrap().to_rfc3339()
+    }
+
+    #[test]
+    fn picks_the_pass_of_the_repeated_hour_after_the_reference() {
+        // 01:30 happens twice on 2026-11-01
+        let first = utc("2026-11-01T01:20:00-04:00");
+        assert_eq!(
+            rfc3339(parse_after("20261101 01:30:00", first)),
+            "2026-11-01T01:30:00-04:00"
+        );
+        let second = utc("2026-11-01T01:10:00-05:00");
+        assert_eq!(
+            rfc3339(parse_after("20261101 01:30:00", second)),
+            "2026-11-01T01:30:00-05:00"
+        );
+        // a prediction a couple of minutes stale is still the first pass
+        let just_after = utc("2026-11-01T01:33:00-04:00");
+        assert_eq!(
+            rfc3339(parse_after("20261101 01:30:00", just_after)),
+            "2026-11-01T01:30:00-04:00"
+        );
+
+        assert_eq!(
+            resolve(NaiveDateTime::parse_from_str("20261101 01:30:00", FORMAT).unwrap())
+                .to_rfc3339(),
+            "2026-11-01T01:30:00-04:00"
+        );
+    }
+
+    #[test]
+    fn moves_skipped_times_an_hour_later() {
+        // 02:30 doesn't happen on 2026-03-08
+        let reference = utc("2026-03-08T01:55:00-05:00");
+        assert_eq!(
+            rfc3339(parse_after("20260308 02:30:00", reference)),
+            "2026-03-08T03:30:00-04:00"
+        );
+        assert_eq!(
+            resolve(NaiveDateTime::parse_from_str("20260308 02:30:00", FORMAT).unwrap())
+                .to_rfc3339(),
+            "2026-03-08T03:30:00-04:00"
+        );
+    }
+
+    #[test]
+    fn rolls_past_midnight() {
+        let reference = utc("2026-10-18T23:58:00-04:00");
+        let arrival = parse_after("20261019 00:05:00", reference).unwrap();
+        assert_eq!(arrival.to_rfc3339(), "2026-10-19T00:05:00-04:00");
+        assert_eq!((arrival.with_timezone(&Utc) - reference).num_minutes(), 7);
+
+        // and into the new year
+        let reference = utc("2026-12-31T23:59:30-05:00");
+        assert_eq!(
+            rfc3339(parse_after("20270101 00:02:00", reference)),
+            "2027-01-01T00:02:00-05:00"
+        );
+        assert_eq!(parse_after("20261019 24:05:00", reference), None);
+    }
+}
@@ -1,108 +1,314 @@
 // BACKEND for CMU bus sign
 // serves data to http://{API_HOST}:{API_PORT}/predictions
-// 20-second cache in place to prevent API abuse
-// (only requests from API every 20 seconds)
-
+// and per sign to http://{API_HOST}:{API_PORT}/signs/{sign_id}/predictions
+// append /stream to either for server-sent events instead of polling (see stream.rs)
+// or subscribe to individual stops over a websocket at /ws (see ws.rs)
+// /vehicles has the positions of the vehicles on the routes serving the stops (see vehicles.rs)
+// /alerts has PRT's service bulletins and detours for them, which are also attached to the
+// route groups they affect (see alerts.rs)
+// /routes has every route's name and color, also attached to route groups (see routes.rs)
+// /stops has each stop's local labels plus PRT's name, direction and place (see stops.rs)
+// /v2/... serves the same data in a self-describing envelope (see v2.rs)
+// a background task polls the prediction source (BusTime by default, or GTFS-rt, see
+// source.rs) on a schedule,
+// requests are always answered from the latest snapshot (see poller.rs)
+// stops are configured at startup, see config.rs
+// outside the configured service hours polling slows or stops and responses say when
+// service resumes (see service.rs)
+// stops with no live data fall back to the GTFS timetable, if one is configured (see schedule.rs)
+
+mod alerts;
+mod budget;
+mod bustime;
+mod circuit;
+mod config;
+mod gtfs;
+mod gtfs_rt;
+mod local_time;
+mod metrics;
+mod poller;
+mod prt_errors;
+mod routes;
+mod schedule;
+mod service;
+mod signs;
+mod source;
+mod stops;
+mod stream;
+mod upstream;
+mod v2;
+mod vehicles;
+mod viewers;
+mod ws;
+
+use alerts::{Alert, Alerts};
 use axum::{
     Json, Router,
-    extract::State,
-    http::StatusCode,
-    response::{IntoResponse, Response},
+    extract::{Path, State},
+    http::{HeaderName, StatusCode, header::AGE},
+    response::{AppendHeaders, IntoResponse, Response},
     routing::get,
 };
-use chrono::{DateTime, Duration, NaiveDateTime, Utc};
-use serde::{Deserialize, Serialize};
+use budget::Budget;
+use bustime::BusTime;
+use chrono::{DateTime, FixedOffset, Utc};
+use config::{Config, SourceConfig, StopConfig};
+use gtfs::GtfsStatic;
+use gtfs_rt::GtfsRt;
+use poller::Poller;
+use prt_errors::PrtErrorKind;
+use routes::Routes;
+use schedule::Schedule;
+use serde::Serialize;
+use service::ServiceStatus;
+use source::{ArrivalKind, Mode, PredictionSource};
 use std::net::{IpAddr, SocketAddr};
-use std::{collections::HashMap, env, sync::Arc};
-use tokio::{signal, sync::Mutex};
+use std::{collections::HashMap, env, str::FromStr, sync::Arc, time::Duration};
+use stops::Stops;
+use tokio::signal;
 use tower_http::cors::{Any, CorsLayer};
+use upstream::{ApiKey, Upstream, UpstreamFailure, UpstreamSettings};
+use vehicles::Vehicles;
+use viewers::Viewers;
 
-// parts of API request URL
+// PRT's BusTime API, see bustime.rs
 const BASE_URL: &str = "http://truetime.portauthority.org/bustime/api/v3";
-const STOPS: &str = "4407,7117"; // stops to retrieve data from
-const TIME_RES: &str = "s"; // resolution of time data (seconds)
-const FEED_NAME: &str = "Port Authority Bus";
 
-// time between cache refreshes
-const CACHE_DURATION_SECONDS: i64 = 20;
+// time between cache refreshes outside rush hour, see budget.rs for the full schedule
+const CACHE_DURATION_SECONDS: u64 = 20;
+
+// a request that finds the snapshot older than this (or twice the current polling interval,
+// if that is longer) refreshes it itself
+const MAX_SNAPSHOT_AGE_SECONDS: u64 = 2 * CACHE_DURATION_SECONDS;
+
+// BusTime's default daily transaction cap per key (override with DAILY_REQUEST_LIMIT)
+const DEFAULT_DAILY_REQUEST_LIMIT: u64 = 10_000;
+// where the day's request count is kept across restarts (override with BUDGET_STATE_PATH)
+const DEFAULT_BUDGET_STATE_PATH: &str = "budget.json";
+
+// how long the last good data is served while PRT is failing (override with STALE_GRACE_SECONDS)
+const DEFAULT_STALE_GRACE_SECONDS: i64 = 120;
+
+// upstream resilience defaults, each overridable from the environment
+const DEFAULT_CONNECT_TIMEOUT_SECONDS: u64 = 3; // UPSTREAM_CONNECT_TIMEOUT_SECONDS
+const DEFAULT_TIMEOUT_SECONDS: u64 = 10; // UPSTREAM_TIMEOUT_SECONDS
+const DEFAULT_RETRIES: u32 = 2; // UPSTREAM_RETRIES
+const DEFAULT_CIRCUIT_FAILURE_THRESHOLD: u32 = 5; // CIRCUIT_FAILURE_THRESHOLD
+const DEFAULT_CIRCUIT_COOLDOWN_SECONDS: u64 = 60; // CIRCUIT_COOLDOWN_SECONDS
+
+// set on responses built from data older than the latest (failed) poll
+const STALE_HEADER: HeaderName = HeaderName::from_static("x-data-stale");
+// whether buses are running, and outside service hours when they start again
+const IN_SERVICE_HEADER: HeaderName = HeaderName::from_static("x-in-service");
+const SERVICE_RESUMES_HEADER: HeaderName = HeaderName::from_static("x-service-resumes-at");
+// comma separated stops whose batch failed in the latest poll, when the rest succeeded
+const FAILED_STOPS_HEADER: HeaderName = HeaderName::from_static("x-failed-stops");
 
 #[derive(Clone)]
 struct AppState {
-    api_key: String,
-    client: reqwest::Client,
-    cache: Arc<Mutex<Cache>>,
+    config: Arc<Config>,
+    upstream: Arc<Upstream>,
+    poller: Arc<Poller>,
+    viewers: Arc<Viewers>,
+    // timetable to fill gaps in live data from, if one is configured
+    schedule: Option<Arc<Schedule>>,
+    // only BusTime knows where vehicles are
+    vehicles: Option<Arc<Vehicles>>,
+    // and only BusTime has service bulletins and detours
+    alerts: Option<Arc<Alerts>>,
+    routes: Arc<Routes>,
+    stops: Arc<Stops>,
+    stale_grace_seconds: i64,
 }
 
-struct Cache {
-    last_update: Option<DateTime<Utc>>,
+// predictions ready to serve, countdowns already adjusted to now
+struct Predictions {
     data: FrontendResponse,
+    // None outside service hours if nothing has been fetched to show
+    fetched_at: Option<DateTime<Utc>>,
+    // seconds since the data was fetched from PRT
+    age_seconds: Option<i64>,
+    // the most recent poll failed and this is older data
+    stale: bool,
+    // why the most recent poll failed, if it did
+    poll_error: Option<AppError>,
+    // error messages PRT sent along with the data
+    messages: Vec<PrtMessage>,
+    // stops missing from the data because their batch failed
+    failures: Vec<StopFailure>,
+    service: ServiceStatus,
 }
 
-enum AppError {
-    UpstreamError(reqwest::Error),
-    JsonError(serde_json::Error),
+impl Predictions {
+    // outside service hours, with no data worth showing
+    fn no_service(service: ServiceStatus) -> Self {
+        Predictions {
+            data: FrontendResponse::new(),
+            fetched_at: None,
+            age_seconds: None,
+            stale: false,
+            poll_error: None,
+            messages: Vec::new(),
+            failures: Vec::new(),
+            service,
+        }
+    }
+
+    // only the timetable, because there is no live data to show
+    fn scheduled(
+        state: &AppState,
+        schedule: &Schedule,
+        error: &AppError,
+        service: ServiceStatus,
+    ) -> Self {
+        let mut data = FrontendResponse::new();
+        let now = Utc::now();
+        schedule.fill_gaps(&mut data, &state.config.stops, now);
+        annotate(state, &mut data, now);
+        Predictions {
+            data,
+            stale: true,
+            poll_error: Some(error.clone()),
+            ..Predictions::no_service(service)
+        }
+    }
 }
 
-impl IntoResponse for AppError {
+impl IntoResponse for Predictions {
     fn into_response(self) -> Response {
-        let (status, error_message) = match self {
+        let mut headers = vec![
+            (STALE_HEADER, self.stale.to_string()),
+            (IN_SERVICE_HEADER, self.service.in_service.to_string()),
+        ];
+        if let Some(age) = self.age_seconds {
+            headers.push((AGE, age.max(0).to_string()));
+        }
+        if let Some(at) = self.service.resumes_at {
+            headers.push((SERVICE_RESUMES_HEADER, at.to_rfc3339()));
+        }
+        if !self.failures.is_empty() {
+            let stops: Vec<&str> = self.failures.iter().map(|f| f.stop.as_str()).collect();
+            headers.push((FAILED_STOPS_HEADER, stops.join(",")));
+        }
+        (AppendHeaders(headers), Json(self.data)).into_response()
+    }
+}
+
+// errors are published to every waiting handler, so they carry messages rather than sources
+// every message is either our own text or upstream text with the API key redacted
+#[derive(Clone, Debug)]
+enum AppError {
+    UpstreamError(UpstreamFailure),
+    ParseError(String),
+    // PRT answered, but with an error that makes the whole response unusable
+    PrtError(PrtErrorKind, String),
+    UnknownSign(String),
+    // the endpoint needs something this configuration doesn't have
+    NotAvailable(&'static str),
+}
+
+impl AppError {
+    // machine readable name for /v2 clients
+    fn code(&self) -> &'static str {
+        match self {
+            AppError::UpstreamError(_) => "upstream_unavailable",
+            AppError::ParseError(_) => "upstream_invalid_response",
+            AppError::PrtError(kind, _) => kind.code(),
+            AppError::UnknownSign(_) => "unknown_sign",
+            AppError::NotAvailable(_) => "not_available",
+        }
+    }
+
+    fn status_and_message(&self) -> (StatusCode, String) {
+        match self {
             AppError::UpstreamError(e) => {
                 (StatusCode::BAD_GATEWAY, format!("API Connect Error: {}", e))
             }
-            AppError::JsonError(e) => (
+            AppError::ParseError(e) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("API Parse Error: {}", e),
             ),
-        };
-        (status, Json(serde_json::json!({ "error": error_message }))).into_response()
+            AppError::PrtError(kind, msg) => (
+                StatusCode::BAD_GATEWAY,
+                format!("PRT API Error ({}): {}", kind.code(), msg),
+            ),
+            AppError::UnknownSign(id) => (StatusCode::NOT_FOUND, format!("Unknown sign: {}", id)),
+            AppError::NotAvailable(why) => (
+                StatusCode::NOT_IMPLEMENTED,
+                format!("Not available: {}", why),
+            ),
+        }
     }
 }
 
-// --- INCOMING DATA (From API) ---
-#[derive(Deserialize, Debug)]
-struct PrtResponse {
-    #[serde(rename = "bustime-response")]
-    response: PrtBody,
-}
-
-#[derive(Deserialize, Debug)]
-struct PrtBody {
-    #[serde(rename = "prd", default)]
-    predictions: Option<Vec<PrtPrediction>>,
-    #[serde(rename = "error", default)]
-    api_error: Option<Vec<PrtError>>,
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, error_message) = self.status_and_message();
+        (status, Json(serde_json::json!({ "error": error_message }))).into_response()
+    }
 }
 
-#[derive(Deserialize, Debug)]
-struct PrtError {
-    msg: String,
+// an error PRT reported inside an otherwise successful response, kept for /v2 clients
+#[derive(Serialize, Debug, Clone)]
+struct PrtMessage {
+    kind: PrtErrorKind,
+    message: String,
+    stop: Option<String>,
+    route: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
-struct PrtPrediction {
-    rt: String,
-    des: String,
-    stpid: String,
-    vid: String,
-    tmstmp: String,
-    prdtm: String,
-    #[serde(default)]
-    psgld: String,
+// a stop left out of a poll because the request for its batch failed
+#[derive(Debug, Clone)]
+struct StopFailure {
+    stop: String,
+    error: AppError,
 }
 
 // --- OUTGOING DATA (To Frontend) ---
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, PartialEq)]
 struct RouteGroup {
     route: String,
+    // the route's full name and color, if known (see routes.rs)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    route_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    color: Option<String>,
     destination: String,
     arrivals: Vec<BusArrival>,
+    // the first of `arrivals` is the route's last bus from this stop tonight (see schedule.rs)
+    last_bus: bool,
+    mode: Mode,
+    // service bulletins and detours for the route at this stop (see alerts.rs)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    alerts: Vec<Alert>,
+    // as the feed gives them ("OUTBOUND", "Forbes Ave opp Morewood Ave"), see source.rs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    direction: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_name: Option<String>,
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, PartialEq)]
 struct BusArrival {
     bus_id: String,
     seconds: i64,
+    // predicted arrival, with Pittsburgh's UTC offset at that moment
+    arrival_at: DateTime<FixedOffset>,
     capacity: String,
+    // realtime, or scheduled when the live feed had nothing for the stop
+    kind: ArrivalKind,
+    // passed through from the feed as of the fetch, see ArrivalDetails in source.rs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    distance_feet: Option<u32>,
+    delayed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    countdown: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trip_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    block_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dynamic_action: Option<u8>,
 }
 
 type FrontendResponse = HashMap<String, Vec<RouteGroup>>;
@@ -112,22 +318,148 @@ async fn main() {
     // load API key from .env in parent directory
     dotenvy::dotenv().ok();
 
-    let api_key = env::var("PRT_API_KEY").expect("PRT_API_KEY must be set in .env");
+    let config = Config::load().unwrap_or_else(|e| panic!("invalid sign configuration: {}", e));
+    println!("Serving stops {}", config.stop_ids());
+
+    // only BusTime needs a key
+    let api_key = ApiKey::new(match config.source {
+        SourceConfig::Bustime { .. } => {
+            env::var("PRT_API_KEY").expect("PRT_API_KEY must be set in .env")
+        }
+        SourceConfig::GtfsRt { .. } => env::var("PRT_API_KEY").unwrap_or_default(),
+    });
+
+    let settings = UpstreamSettings {
+        connect_timeout: Duration::from_secs(env_or(
+            "UPSTREAM_CONNECT_TIMEOUT_SECONDS",
+            DEFAULT_CONNECT_TIMEOUT_SECONDS,
+        )),
+        timeout: Duration::from_secs(env_or("UPSTREAM_TIMEOUT_SECONDS", DEFAULT_TIMEOUT_SECONDS)),
+        retries: env_or("UPSTREAM_RETRIES", DEFAULT_RETRIES),
+        failure_threshold: env_or(
+            "CIRCUIT_FAILURE_THRESHOLD",
+            DEFAULT_CIRCUIT_FAILURE_THRESHOLD,
+        ),
+        cooldown: Duration::from_secs(env_or(
+            "CIRCUIT_COOLDOWN_SECONDS",
+            DEFAULT_CIRCUIT_COOLDOWN_SECONDS,
+        )),
+    };
+
+    let budget = Budget::load(
+        api_key.expose(),
+        env_or("DAILY_REQUEST_LIMIT", DEFAULT_DAILY_REQUEST_LIMIT),
+        env::var("BUDGET_STATE_PATH").unwrap_or_else(|_| DEFAULT_BUDGET_STATE_PATH.to_string()),
+    );
+
+    let config = Arc::new(config);
+    let upstream = Arc::new(Upstream::new(api_key, &settings, budget));
+    let viewers = Arc::new(Viewers::default());
+    let timetable = match config.timetable() {
+        Some(location) => Some(load_gtfs(&upstream, location, &config.stops).await),
+        None => None,
+    };
+    let source: Arc<dyn PredictionSource> = match &config.source {
+        SourceConfig::Bustime { feeds } => Arc::new(BusTime::new(upstream.clone(), feeds)),
+        SourceConfig::GtfsRt {
+            trip_updates,
+            gtfs_static,
+        } => {
+            // the same feed is only loaded once
+            let gtfs = match &timetable {
+                Some(gtfs) if config.timetable() == Some(gtfs_static.as_str()) => gtfs.clone(),
+                _ => load_gtfs(&upstream, gtfs_static, &config.stops).await,
+            };
+            Arc::new(GtfsRt::new(
+                trip_updates.clone(),
+                gtfs,
+                upstream.client().clone(),
+                &config.stops,
+            ))
+        }
+    };
+    let schedule = timetable.map(|gtfs| Arc::new(Schedule::new(gtfs)));
+    let vehicles = match &config.source {
+        SourceConfig::Bustime { feeds } => Some(Arc::new(Vehicles::new(upstream.clone(), feeds))),
+        SourceConfig::GtfsRt { .. } => None,
+    };
+    let poller = Poller::spawn(source, config.clone(), viewers.clone());
+    let overrides = match &config.routes {
+        Some(routes) => routes::load_overrides(&routes.overrides)
+            .unwrap_or_else(|e| panic!("invalid route overrides: {}", e)),
+        None => HashMap::new(),
+    };
+    let routes = match &config.source {
+        SourceConfig::Bustime { feeds } => Routes::spawn(Some(upstream.clone()), feeds, overrides),
+        SourceConfig::GtfsRt { .. } => Routes::spawn(None, &[], overrides),
+    };
+    let stops = match &config.source {
+        SourceConfig::Bustime { feeds } => Stops::spawn(
+            Some(upstream.clone()),
+            feeds,
+            config.clone(),
+            routes.clone(),
+        ),
+        SourceConfig::GtfsRt { .. } => Stops::spawn(None, &[], config.clone(), routes.clone()),
+    };
+    let alerts = match &config.source {
+        SourceConfig::Bustime { feeds } => Some(Alerts::spawn(
+            upstream.clone(),
+            feeds,
+            config.clone(),
+            poller.clone(),
+            stops.clone(),
+        )),
+        SourceConfig::GtfsRt { .. } => None,
+    };
+
+    let stale_grace_seconds = env_or("STALE_GRACE_SECONDS", DEFAULT_STALE_GRACE_SECONDS);
 
     let state = AppState {
-        api_key,
-        client: reqwest::Client::new(),
-        cache: Arc::new(Mutex::new(Cache {
-            last_update: None,
-            data: HashMap::new(),
-        })),
+        config,
+        upstream,
+        poller,
+        viewers,
+        schedule,
+        vehicles,
+        alerts,
+        routes,
+        stops,
+        stale_grace_seconds,
     };
 
     // cors for security - allow(Any) is fine for this but not best practice (fix before prod)
-    let cors = CorsLayer::new().allow_origin(Any).allow_methods(Any);
+    let cors = CorsLayer::new()
+        .allow_origin(Any)
+        .allow_methods(Any)
+        .expose_headers([
+            AGE,
+            STALE_HEADER,
+            IN_SERVICE_HEADER,
+            SERVICE_RESUMES_HEADER,
+            FAILED_STOPS_HEADER,
+        ]);
 
     let app = Router::new()
         .route("/predictions", get(get_predictions))
+        .route("/predictions/stream", get(stream::get_predictions_stream))
+        .route("/stops", get(stops::get_stops))
+        .route("/ws", get(ws::get_ws))
+        .route("/vehicles", get(vehicles::get_vehicles))
+        .route("/alerts", get(alerts::get_alerts))
+        .route("/routes", get(routes::get_routes))
+        .route("/metrics", get(metrics::get_metrics))
+        .route("/signs/:sign_id/predictions", get(get_sign_predictions))
+        .route(
+            "/signs/:sign_id/predictions/stream",
+            get(stream::get_sign_predictions_stream),
+        )
+        .route("/signs/:sign_id/stops", get(stops::get_sign_stops))
+        .route("/v2/predictions", get(v2::get_predictions))
+        .route(
+            "/v2/signs/:sign_id/predictions",
+            get(v2::get_sign_predictions),
+        )
         .layer(cors)
         .with_state(state);
 
@@ -150,6 +482,24 @@ async fn main() {
         .unwrap();
 }
 
+// optional numeric setting from the environment
+fn env_or<T: FromStr>(name: &str, default: T) -> T {
+    match env::var(name) {
+        Ok(value) => value
+            .parse()
+            .unwrap_or_else(|_| panic!("{} must be a number, got {:?}", name, value)),
+        Err(_) => default,
+    }
+}
+
+// a GTFS static feed the sign can't run without
+async fn load_gtfs(upstream: &Upstream, location: &str, stops: &[StopConfig]) -> Arc<GtfsStatic> {
+    let gtfs = GtfsStatic::load(upstream.client(), location, stops)
+        .await
+        .unwrap_or_else(|e| panic!("could not load GTFS static feed: {}", e));
+    Arc::new(gtfs)
+}
+
 // Adding a handler for shutdown signals
 async fn shutdown_signal() {
     let ctrl_c = async {
@@ -175,114 +525,221 @@ async fn shutdown_signal() {
     }
 }
 
-async fn get_predictions(
+// every configured stop, unfiltered
+async fn get_predictions(State(state): State<AppState>) -> Result<Predictions, AppError> {
+    current_predictions(&state).await
+}
+
+// predictions filtered and ordered for one sign profile
+async fn get_sign_predictions(
     State(state): State<AppState>,
-) -> Result<Json<FrontendResponse>, AppError> {
-    {
-        let cache = state.cache.lock().await;
-        if let Some(last_update) = cache.last_update {
-            let now = Utc::now();
-            let elapsed = now.signed_duration_since(last_update);
-            if elapsed < Duration::seconds(CACHE_DURATION_SECONDS) {
-                println!("Returning cached data");
-
-                let mut response_data = cache.data.clone();
-
-                let elapsed_seconds = elapsed.num_seconds();
-
-                // if pulling from cache, linearly decreases predicted times according to real time elapsed
-                for route_groups in response_data.values_mut() {
-                    for group in route_groups {
-                        for arrival in &mut group.arrivals {
-                            if arrival.seconds > 30 {
-                                arrival.seconds -= elapsed_seconds;
-                            }
-                        }
-                    }
-                }
+    Path(sign_id): Path<String>,
+) -> Result<Predictions, AppError> {
+    let sign = state
+        .config
+        .sign(&sign_id)
+        .ok_or(AppError::UnknownSign(sign_id))?;
+
+    let predictions = current_predictions(&state).await?;
+    Ok(Predictions {
+        data: signs::view(sign, &predictions.data),
+        ..predictions
+    })
+}
 
-                return Ok(Json(response_data));
-            }
+// all signs share one snapshot, so stops shown on several signs are only fetched once
+// if the latest poll failed, the last good snapshot is served (flagged stale) for up to
+// stale_grace_seconds before the upstream error is passed on
+async fn current_predictions(state: &AppState) -> Result<Predictions, AppError> {
+    state.viewers.touch();
+    let service = state.config.service_status(Utc::now());
+
+    let poll = if state.config.polling_suspended(Utc::now()) {
+        // requests mustn't restart polling before service resumes
+        match state.poller.latest() {
+            Some(poll) => poll,
+            None => return Ok(Predictions::no_service(service)),
         }
-    }
-
-    println!("Fetching from API");
-    let url = format!(
-        "{}/getpredictions?key={}&stpid={}&tmres={}&rtpidatafeed={}&format=json",
-        BASE_URL, state.api_key, STOPS, TIME_RES, FEED_NAME
-    );
+    } else {
+        // only waits if there is no recent snapshot, sharing any fetch already in flight
+        let max_age = Duration::from_secs(MAX_SNAPSHOT_AGE_SECONDS).max(2 * state.poller.period());
+        state.poller.refresh_if_older_than(max_age).await
+    };
 
-    let resp = state
-        .client
-        .get(&url)
-        .send()
-        .await
-        .map_err(AppError::UpstreamError)?;
+    let snapshot = match (&poll.snapshot, &poll.error) {
+        (Some(snapshot), None) => snapshot,
+        (Some(snapshot), Some(_)) if snapshot.age_seconds() <= state.stale_grace_seconds => {
+            snapshot
+        }
+        // no buses to show either way, so say why rather than pass on the error
+        (_, Some(_)) if !service.in_service => return Ok(Predictions::no_service(service)),
+        // the timetable still knows roughly when buses should come
+        (_, Some(e)) => match &state.schedule {
+            Some(schedule) => return Ok(Predictions::scheduled(state, schedule, e, service)),
+            None => return Err(e.clone()),
+        },
+        (None, None) => unreachable!("every poll has either a snapshot or an error"),
+    };
+    let elapsed_seconds = snapshot.age_seconds();
 
-    let raw_text = resp.text().await.map_err(AppError::UpstreamError)?;
-    let clean_text = raw_text.replace(r"\", "/");
-    let prt_data: PrtResponse = serde_json::from_str(&clean_text).map_err(AppError::JsonError)?;
+    let mut response_data = snapshot.data.clone();
 
-    if let Some(errors) = prt_data.response.api_error {
-        for err in errors {
-            println!("PRT API Error Message: {}", err.msg);
+    // linearly decreases predicted times according to real time elapsed since the fetch
+    for route_groups in response_data.values_mut() {
+        for group in route_groups.iter_mut() {
+            group.arrivals.retain_mut(|arrival| {
+                let remaining = arrival.seconds - elapsed_seconds;
+                if arrival.seconds > 30 {
+                    arrival.seconds = remaining.max(0);
+                }
+                // only stale data gets this old: the bus has certainly left by now
+                remaining >= -(MAX_SNAPSHOT_AGE_SECONDS as i64)
+            });
         }
+        route_groups.retain(|group| !group.arrivals.is_empty());
+    }
+    let now = Utc::now();
+    if let Some(schedule) = &state.schedule {
+        schedule.fill_gaps(&mut response_data, &state.config.stops, now);
+    }
+    annotate(state, &mut response_data, now);
+
+    Ok(Predictions {
+        data: response_data,
+        fetched_at: Some(snapshot.fetched_at),
+        age_seconds: Some(elapsed_seconds),
+        stale: poll.error.is_some(),
+        poll_error: poll.error.clone(),
+        messages: snapshot.messages.clone(),
+        failures: snapshot.failures.clone(),
+        service,
+    })
+}
+
+// what every response adds to the grouped arrivals, live or scheduled
+fn annotate(state: &AppState, data: &mut FrontendResponse, now: DateTime<Utc>) {
+    if let Some(schedule) = &state.schedule {
+        schedule.mark_last_buses(data, now);
+    }
+    if let Some(alerts) = &state.alerts {
+        alerts.attach(data, now);
+    }
+    state.routes.attach(data);
+}
 
-        {
-            let mut cache = state.cache.lock().await;
-            cache.data = HashMap::new();
-            cache.last_update = Some(Utc::now());
+// the app with the default config, serving `source` and nothing else, for handler tests
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    // once the startup poll has finished
+    pub(crate) async fn state(source: Arc<dyn PredictionSource>) -> AppState {
+        let config = Arc::new(Config::default());
+        let settings = UpstreamSettings {
+            connect_timeout: Duration::from_secs(1),
+            timeout: Duration::from_secs(1),
+            retries: 0,
+            failure_threshold: DEFAULT_CIRCUIT_FAILURE_THRESHOLD,
+            cooldown: Duration::from_secs(DEFAULT_CIRCUIT_COOLDOWN_SECONDS),
+        };
+        // nothing is requested from PRT, so nothing is ever written here
+        let budget_path =
+            env::temp_dir().join(format!("budget-unused-{}.json", std::process::id()));
+        let budget = Budget::load("", 1000, budget_path.to_string_lossy().into_owned());
+        let viewers = Arc::new(Viewers::default());
+        let poller = Poller::spawn(source, config.clone(), viewers.clone());
+        poller.subscribe().wait_for(Option::is_some).await.unwrap();
+        let routes = Routes::spawn(None, &[], HashMap::new());
+
+        AppState {
+            config: config.clone(),
+            upstream: Arc::new(Upstream::new(ApiKey::new(String::new()), &settings, budget)),
+            poller: poller.clone(),
+            viewers,
+            schedule: None,
+            vehicles: None,
+            alerts: None,
+            stops: Stops::spawn(None, &[], config, routes.clone()),
+            routes,
+            stale_grace_seconds: DEFAULT_STALE_GRACE_SECONDS,
         }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bustime::parse_predictions;
 
-        return Ok(Json(HashMap::new()));
+    const KEY: &str = "sEcReTkEy123";
+
+    async fn body_of(error: AppError) -> String {
+        let response = error.into_response();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
     }
 
-    let mut output: FrontendResponse = HashMap::new();
-
-    if let Some(predictions) = prt_data.response.predictions {
-        // handle API data
-        for p in predictions.into_iter() {
-            let format = "%Y%m%d %H:%M:%S";
-            let seconds_left = match (
-                NaiveDateTime::parse_from_str(&p.tmstmp, format),
-                NaiveDateTime::parse_from_str(&p.prdtm, format),
-            ) {
-                (Ok(s), Ok(a)) => a.signed_duration_since(s).num_seconds(),
-                _ => continue, // skip this iteration if bad time data
-                               // TODO: add some kind of internal warning here
-            };
+    // nothing listens on the discard port, so this fails with the key in the request URL
+    async fn failed_request() -> reqwest::Error {
+        reqwest::Client::new()
+            .get("http://127.0.0.1:9/getpredictions")
+            .query(&[("key", KEY)])
+            .send()
+            .await
+            .unwrap_err()
+    }
 
-            let stop_list = output.entry(p.stpid).or_default();
+    #[tokio::test]
+    async fn connect_errors_do_not_leak_key() {
+        let error = failed_request().await;
+        assert!(error.to_string().contains(KEY), "reqwest echoes the URL");
 
-            // data for each bus
-            let arrival = BusArrival {
-                bus_id: p.vid,
-                seconds: seconds_left,
-                capacity: p.psgld,
-            };
+        let failure = UpstreamFailure::from_reqwest(error, &ApiKey::new(KEY.to_string()));
+        let body = body_of(AppError::UpstreamError(failure)).await;
+        assert!(!body.contains(KEY), "{}", body);
+    }
 
-            // if stop data already exists, update it; otherwise, make new
-            if let Some(group) = stop_list
-                .iter_mut()
-                .find(|g| g.route == p.rt && g.destination == p.des)
-            {
-                group.arrivals.push(arrival);
-                group.arrivals.sort_by_key(|b| b.seconds);
-            } else {
-                stop_list.push(RouteGroup {
-                    route: p.rt,
-                    destination: p.des,
-                    arrivals: vec![arrival],
-                });
-            }
-        }
+    #[tokio::test]
+    async fn parse_errors_do_not_leak_key() {
+        let key = ApiKey::new(KEY.to_string());
+        let raw = format!("<html>bad request for key={}</html>", KEY);
+
+        let error = parse_predictions(&raw, &key, Mode::Bus).unwrap_err();
+        assert!(!body_of(error).await.contains(KEY));
+
+        let raw = format!(r#"{{"bustime-response": {{"prd": "{}"}}}}"#, KEY);
+        let error = parse_predictions(&raw, &key, Mode::Bus).unwrap_err();
+        assert!(!body_of(error).await.contains(KEY));
     }
 
-    {
-        let mut cache = state.cache.lock().await;
-        cache.data = output.clone();
-        cache.last_update = Some(Utc::now());
+    #[tokio::test]
+    async fn prt_messages_do_not_leak_key() {
+        let key = ApiKey::new(KEY.to_string());
+        let raw = format!(
+            r#"{{"bustime-response": {{"error": [{{"msg": "Invalid API access key supplied: {}"}}]}}}}"#,
+            KEY
+        );
+
+        let error = parse_predictions(&raw, &key, Mode::Bus).unwrap_err();
+        assert!(matches!(
+            error,
+            AppError::PrtError(PrtErrorKind::InvalidKey, _)
+        ));
+        assert!(!body_of(error).await.contains(KEY));
+
+        let raw = format!(
+            r#"{{"bustime-response": {{"error": [{{"stpid": "{}", "msg": "No data found for parameter {}"}}]}}}}"#,
+            KEY, KEY
+        );
+        let (_, messages) = parse_predictions(&raw, &key, Mode::Bus).unwrap();
+        assert!(!format!("{:?}", messages).contains(KEY));
     }
 
-    Ok(Json(output))
+    #[test]
+    fn api_key_debug_is_redacted() {
+        let key = ApiKey::new(KEY.to_string());
+        assert!(!format!("{:?}", key).contains(KEY));
+    }
 }
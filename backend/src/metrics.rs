@@ -0,0 +1,95 @@
+// /metrics in the Prometheus text format, for watching how hard we lean on PRT
+
+use crate::AppState;
+use crate::circuit::CircuitState;
+use axum::{extract::State, http::header::CONTENT_TYPE, response::IntoResponse};
+use std::{fmt::Write, sync::atomic::Ordering};
+
+pub async fn get_metrics(State(state): State<AppState>) -> impl IntoResponse {
+    let stats = &state.upstream.stats;
+    let circuit = state.upstream.circuit();
+    let mut out = String::new();
+
+    let counters = [
+        (
+            "upstream_requests_total",
+            "Requests sent to PRT, including retries",
+            stats.requests.load(Ordering::Relaxed),
+        ),
+        (
+            "upstream_retries_total",
+            "Requests to PRT that were retries of a transient failure",
+            stats.retries.load(Ordering::Relaxed),
+        ),
+        (
+            "upstream_failures_total",
+            "PRT requests that failed after all retries",
+            stats.failures.load(Ordering::Relaxed),
+        ),
+        (
+            "upstream_short_circuited_total",
+            "PRT requests skipped because the circuit was open",
+            stats.short_circuited.load(Ordering::Relaxed),
+        ),
+        (
+            "upstream_circuit_opened_total",
+            "Times the circuit breaker opened",
+            circuit.times_opened,
+        ),
+    ];
+    for (name, help, value) in counters {
+        write_metric(&mut out, name, help, "counter", value);
+    }
+
+    let open = match circuit.state {
+        CircuitState::Closed => 0,
+        CircuitState::Open => 1,
+        CircuitState::HalfOpen => 2,
+    };
+    write_metric(
+        &mut out,
+        "upstream_circuit_state",
+        "Circuit breaker state (0 closed, 1 open, 2 half open)",
+        "gauge",
+        open,
+    );
+    write_metric(
+        &mut out,
+        "upstream_consecutive_failures",
+        "Failed PRT requests since the last success",
+        "gauge",
+        circuit.consecutive_failures.into(),
+    );
+
+    let budget = state.upstream.budget().status();
+    write_metric(
+        &mut out,
+        "upstream_budget_used",
+        "PRT requests made today against the daily limit",
+        "gauge",
+        budget.used,
+    );
+    write_metric(
+        &mut out,
+        "upstream_budget_limit",
+        "Daily PRT request limit for the key",
+        "gauge",
+        budget.limit,
+    );
+    write_metric(
+        &mut out,
+        "poll_interval_seconds",
+        "Current time between background polls",
+        "gauge",
+        state.poller.period().as_secs(),
+    );
+
+    ([(CONTENT_TYPE, "text/plain; version=0.0.4")], out)
+}
+
+fn write_metric(out: &mut String, name: &str, help: &str, kind: &str, value: u64) {
+    // writing to a String can't fail
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    let _ = writeln!(out, "{} {}", name, value);
+}
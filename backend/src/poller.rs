@@ -0,0 +1,285 @@
+// background polling
+// fetches every configured stop from the prediction source (see source.rs) on a schedule the
+// source sets, for BusTime by its request budget (see budget.rs), and publishes the result, so request handlers normally answer instantly from the latest snapshot
+//
+// handlers only fetch themselves if the snapshot is missing or far too old (startup, stalled
+// poller); every concurrent refresh is coalesced into one upstream request whose result all
+// waiters share
+//
+// some PRT errors (bad key, quota used up, PRT outage) hold off polling for longer than usual,
+// see prt_errors.rs
+//
+// outside the configured service hours (see service.rs) polls are spaced out further, or
+// stop altogether until service resumes
+
+use crate::config::Config;
+use crate::source::{self, PredictionSource};
+use crate::viewers::Viewers;
+use crate::{AppError, FrontendResponse, PrtMessage, StopFailure};
+use chrono::{DateTime, Utc};
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+use tokio::{
+    sync::{Mutex, watch},
+    time::{Instant, sleep},
+};
+
+// longest sleep while polling is suspended for the night
+const SUSPENDED_RECHECK: Duration = Duration::from_secs(60 * 60);
+
+pub struct Snapshot {
+    pub fetched_at: DateTime<Utc>,
+    pub data: FrontendResponse,
+    pub messages: Vec<PrtMessage>,
+    // stops whose batch failed this time, the rest of the data is still good
+    pub failures: Vec<StopFailure>,
+}
+
+impl Snapshot {
+    pub fn age_seconds(&self) -> i64 {
+        Utc::now()
+            .signed_duration_since(self.fetched_at)
+            .num_seconds()
+    }
+}
+
+// outcome of one upstream fetch
+pub struct Poll {
+    // when the fetch started, used to decide whether a waiter can reuse it
+    pub started: Instant,
+    // most recent successful fetch, carried over when a poll fails
+    pub snapshot: Option<Arc<Snapshot>>,
+    // why this poll failed, if it did
+    pub error: Option<AppError>,
+    // PRT asked us (by the kind of error it sent) to wait longer than usual before the next poll
+    hold_off: Option<Duration>,
+}
+
+// None until the first poll finishes
+pub type Published = Option<Arc<Poll>>;
+
+pub struct Poller {
+    source: Arc<dyn PredictionSource>,
+    config: Arc<Config>,
+    viewers: Arc<Viewers>,
+    tx: watch::Sender<Published>,
+    // held for the duration of an upstream fetch, so only one is ever in flight
+    fetch_lock: Mutex<()>,
+    // the interval the background task is currently polling at, in milliseconds
+    period_ms: AtomicU64,
+}
+
+impl Poller {
+    pub fn spawn(
+        source: Arc<dyn PredictionSource>,
+        config: Arc<Config>,
+        viewers: Arc<Viewers>,
+    ) -> Arc<Self> {
+        let poller = Arc::new(Poller {
+            source,
+            config,
+            viewers,
+            tx: watch::channel(None).0,
+            fetch_lock: Mutex::new(()),
+            period_ms: AtomicU64::new(0),
+        });
+
+        let background = poller.clone();
+        tokio::spawn(async move {
+            if !background.config.polling_suspended(Utc::now()) {
+                background.refresh_if_older_than(Duration::ZERO).await;
+            }
+
+            let mut was_suspended = false;
+            loop {
+                let period = background.update_period();
+                tokio::select! {
+                    _ = sleep(period) => {}
+                    // someone started watching a sign while we were polling slowly
+                    _ = background.viewers.woken() => {}
+                }
+
+                // nothing to fetch until service resumes, however many viewers show up
+                if background.config.polling_suspended(Utc::now()) {
+                    if !was_suspended {
+                        // let open streams know service has ended
+                        background.tx.send_modify(|_| {});
+                        was_suspended = true;
+                    }
+                    continue;
+                }
+                was_suspended = false;
+
+                let period = background.update_period();
+                // skip this poll if a handler already refreshed during the last half period
+                background.refresh_if_older_than(period / 2).await;
+            }
+        });
+
+        poller
+    }
+
+    // how long the background task currently waits between polls
+    pub fn period(&self) -> Duration {
+        Duration::from_millis(self.period_ms.load(Ordering::Relaxed))
+    }
+
+    fn update_period(&self) -> Duration {
+        let now = Utc::now();
+        let budgeted = self
+            .source
+            .poll_interval(self.viewers.is_idle(), self.config.stops.len());
+
+        let service = self.config.service_status(now);
+        let off_hours = self
+            .config
+            .service
+            .as_ref()
+            .and_then(|s| s.off_hours_poll());
+        let period = if service.in_service {
+            budgeted
+        } else if let Some(off_hours) = off_hours {
+            budgeted.max(off_hours)
+        } else {
+            // suspended: wake when service resumes, checking in at least hourly
+            service
+                .resumes_at
+                .and_then(|at| (at.with_timezone(&Utc) - now).to_std().ok())
+                .map_or(SUSPENDED_RECHECK, |wait| wait.min(SUSPENDED_RECHECK))
+        };
+        self.period_ms
+            .store(period.as_millis() as u64, Ordering::Relaxed);
+        period
+    }
+
+    pub fn source_name(&self) -> &'static str {
+        self.source.name()
+    }
+
+    // the latest poll, without refreshing it
+    pub fn latest(&self) -> Published {
+        self.tx.borrow().clone()
+    }
+
+    // notified after every poll, successful or not
+    pub fn subscribe(&self) -> watch::Receiver<Published> {
+        self.tx.subscribe()
+    }
+
+    // latest poll if it started within max_age, otherwise fetches a new one
+    pub async fn refresh_if_older_than(&self, max_age: Duration) -> Arc<Poll> {
+        if let Some(poll) = self.fresh(max_age) {
+            return poll;
+        }
+
+        let _guard = self.fetch_lock.lock().await;
+
+        // whoever held the lock before us may have just finished the fetch we were waiting on
+        if let Some(poll) = self.fresh(max_age) {
+            return poll;
+        }
+
+        let started = Instant::now();
+        let poll = match self.source.fetch(&self.config.stops).await {
+            Ok(update) => Poll {
+                started,
+                snapshot: Some(Arc::new(Snapshot {
+                    fetched_at: Utc::now(),
+                    data: source::group(update.arrivals),
+                    messages: update.messages,
+                    failures: update.failures,
+                })),
+                error: None,
+                hold_off: None,
+            },
+            Err(e) => {
+                println!("Poll failed: {:?}", e);
+                let hold_off = match &e {
+                    AppError::PrtError(kind, _) => kind.hold_off(),
+                    _ => None,
+                };
+                Poll {
+                    started,
+                    snapshot: self.tx.borrow().as_ref().and_then(|p| p.snapshot.clone()),
+                    error: Some(e),
+                    hold_off,
+                }
+            }
+        };
+
+        let poll = Arc::new(poll);
+        // send_replace keeps publishing even while nobody is subscribed
+        self.tx.send_replace(Some(poll.clone()));
+        poll
+    }
+
+    fn fresh(&self, max_age: Duration) -> Option<Arc<Poll>> {
+        self.tx
+            .borrow()
+            .as_ref()
+            .filter(|poll| poll.started.elapsed() < max_age.max(poll.hold_off.unwrap_or_default()))
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UpstreamFailure;
+    use crate::source::testing::{InMemorySource, arrival};
+
+    // a poller that has finished its startup poll
+    async fn spawn(source: &Arc<InMemorySource>) -> Arc<Poller> {
+        let poller = Poller::spawn(
+            source.clone(),
+            Arc::new(Config::default()),
+            Arc::new(Viewers::default()),
+        );
+        poller.subscribe().wait_for(Option::is_some).await.unwrap();
+        poller
+    }
+
+    #[tokio::test]
+    async fn concurrent_refreshes_share_one_fetch() {
+        let source = Arc::new(InMemorySource::new(vec![arrival(
+            "7117", "61C", "Downtown", 5,
+        )]));
+        let poller = spawn(&source).await;
+        sleep(Duration::from_millis(200)).await;
+
+        let polls = futures_util::future::join_all(
+            (0..10).map(|_| poller.refresh_if_older_than(Duration::from_millis(100))),
+        )
+        .await;
+
+        // one refresh after the startup poll, every other caller waited for it
+        assert_eq!(source.fetch_count(), 2);
+        assert!(polls.iter().all(|poll| Arc::ptr_eq(poll, &polls[0])));
+        let snapshot = polls[0].snapshot.as_ref().unwrap();
+        assert_eq!(snapshot.data["7117"][0].route, "61C");
+    }
+
+    #[tokio::test]
+    async fn failed_poll_keeps_last_snapshot() {
+        let source = Arc::new(InMemorySource::new(vec![arrival(
+            "4407", "67", "Downtown", 9,
+        )]));
+        let poller = spawn(&source).await;
+        let first = poller.latest().unwrap();
+
+        *source.result.lock().unwrap() = Err(AppError::UpstreamError(UpstreamFailure::Timeout));
+        let failed = poller.refresh_if_older_than(Duration::ZERO).await;
+
+        assert_eq!(source.fetch_count(), 2);
+        assert!(failed.error.is_some());
+        assert!(Arc::ptr_eq(
+            failed.snapshot.as_ref().unwrap(),
+            first.snapshot.as_ref().unwrap()
+        ));
+    }
+}
@@ -0,0 +1,155 @@
+// classification of the error messages BusTime puts in "bustime-response.error"
+// the same field carries everything from "no buses tonight" to "your key is invalid", so each
+// message is sorted into a kind that decides how the poller and the clients treat it
+
+use serde::Serialize;
+use std::time::Duration;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PrtErrorKind {
+    // nothing scheduled at this stop right now; a normal answer, not a failure
+    NoService,
+    // the stop (or route) ID isn't known to PRT, almost certainly a config mistake
+    InvalidStop,
+    InvalidKey,
+    // the daily transaction limit for the key has been used up
+    RateLimited,
+    // PRT's own backend is failing
+    SystemError,
+    Other,
+}
+
+impl PrtErrorKind {
+    // BusTime only gives free text, so this matches on the messages it is known to send
+    pub fn classify(message: &str) -> Self {
+        let msg = message.to_lowercase();
+
+        // specific phrases before the key ones: a rate limit message can mention the key
+        if msg.contains("no service scheduled") || msg.contains("no arrival times") {
+            PrtErrorKind::NoService
+        } else if msg.contains("transaction limit") || msg.contains("rate limit") {
+            PrtErrorKind::RateLimited
+        } else if msg.contains("no data found for parameter")
+            || msg.contains("invalid stop")
+            || msg.contains("invalid route")
+            || msg.contains("invalid parameter")
+        {
+            PrtErrorKind::InvalidStop
+        } else if msg.contains("api access key")
+            || msg.contains("api key")
+            || msg.contains("invalid key")
+            || msg.contains("key is invalid")
+        {
+            PrtErrorKind::InvalidKey
+        } else if msg.contains("internal server error")
+            || msg.contains("system error")
+            || msg.contains("unavailable")
+        {
+            PrtErrorKind::SystemError
+        } else {
+            PrtErrorKind::Other
+        }
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            PrtErrorKind::NoService => "no_service",
+            PrtErrorKind::InvalidStop => "invalid_stop",
+            PrtErrorKind::InvalidKey => "invalid_key",
+            PrtErrorKind::RateLimited => "rate_limited",
+            PrtErrorKind::SystemError => "prt_system_error",
+            PrtErrorKind::Other => "prt_error",
+        }
+    }
+
+    // failed polls keep the previous snapshot (served as stale) instead of replacing it
+    // with an empty one; the other kinds are per-stop answers that sit alongside good data
+    pub fn fails_poll(self) -> bool {
+        matches!(
+            self,
+            PrtErrorKind::InvalidKey | PrtErrorKind::RateLimited | PrtErrorKind::SystemError
+        )
+    }
+
+    // how long to wait before asking PRT again, if longer than the normal polling interval
+    pub fn hold_off(self) -> Option<Duration> {
+        match self {
+            // retrying won't fix the key, so only check back occasionally in case it is reissued
+            PrtErrorKind::InvalidKey => Some(Duration::from_secs(5 * 60)),
+            // every retry would count against the exhausted quota
+            PrtErrorKind::RateLimited => Some(Duration::from_secs(15 * 60)),
+            PrtErrorKind::SystemError => Some(Duration::from_secs(60)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_known_messages() {
+        for (message, kind) in [
+            ("No service scheduled", PrtErrorKind::NoService),
+            ("No arrival times", PrtErrorKind::NoService),
+            ("Invalid API access key supplied", PrtErrorKind::InvalidKey),
+            ("No API access key supplied", PrtErrorKind::InvalidKey),
+            ("The API key is invalid", PrtErrorKind::InvalidKey),
+            (
+                "Transaction limit for current day has been exceeded.",
+                PrtErrorKind::RateLimited,
+            ),
+            (
+                "Transaction limit for this key has been exceeded",
+                PrtErrorKind::RateLimited,
+            ),
+            ("No data found for parameter", PrtErrorKind::InvalidStop),
+            ("Invalid stop ID", PrtErrorKind::InvalidStop),
+            (
+                "Invalid parameter provided: stpid",
+                PrtErrorKind::InvalidStop,
+            ),
+            ("Internal Server Error", PrtErrorKind::SystemError),
+            ("Service temporarily unavailable", PrtErrorKind::SystemError),
+            ("Monkey business", PrtErrorKind::Other),
+            ("", PrtErrorKind::Other),
+        ] {
+            assert_eq!(PrtErrorKind::classify(message), kind, "{:?}", message);
+        }
+    }
+
+    #[test]
+    fn only_account_and_system_errors_fail_the_poll() {
+        let failing = [
+            PrtErrorKind::InvalidKey,
+            PrtErrorKind::RateLimited,
+            PrtErrorKind::SystemError,
+        ];
+        for kind in [
+            PrtErrorKind::NoService,
+            PrtErrorKind::InvalidStop,
+            PrtErrorKind::Other,
+        ] {
+            assert!(!kind.fails_poll(), "{:?}", kind);
+            assert_eq!(kind.hold_off(), None, "{:?}", kind);
+        }
+        for kind in failing {
+            assert!(kind.fails_poll(), "{:?}", kind);
+        }
+
+        assert_eq!(
+            PrtErrorKind::InvalidKey.hold_off(),
+            Some(Duration::from_secs(300))
+        );
+        assert_eq!(
+            PrtErrorKind::RateLimited.hold_off(),
+            Some(Duration::from_secs(900))
+        );
+        assert_eq!(
+            PrtErrorKind::SystemError.hold_off(),
+            Some(Duration::from_secs(60))
+        );
+    }
+}
@@ -0,0 +1,278 @@
+// /routes: each route's name and color, so the sign never hard-codes them
+// they come from BusTime's getroutes, asked of every feed, with the local overrides file
+// (see config.rs) on top; a GTFS-rt source has only the overrides
+// they are also attached to every route group on the way out, and each feed's list is what
+// the stops lookup scans (see stops.rs)
+//
+// PRT rarely changes routes, so a background task fetches them at startup and then once every
+// ROUTE_REFRESH_SECONDS, or ROUTE_RETRY_SECONDS after a failed fetch, keeping what it had
+
+use crate::config::FeedConfig;
+use crate::source::Mode;
+use crate::upstream::Upstream;
+use crate::{AppState, FrontendResponse, bustime};
+use axum::{Json, extract::State};
+use chrono::{DateTime, Utc};
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::sleep;
+
+// time between fetches
+const ROUTE_REFRESH_SECONDS: u64 = 24 * 60 * 60;
+
+// and after one that failed
+const ROUTE_RETRY_SECONDS: u64 = 10 * 60;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RouteInfo {
+    // as in predictions ("61C")
+    pub route: String,
+    // the full name ("McKeesport - Homestead")
+    pub name: Option<String>,
+    // "#rrggbb"
+    pub color: Option<String>,
+    pub mode: Mode,
+}
+
+// one route's table in the overrides file; anything left out keeps the source's value
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct RouteOverride {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    color: Option<String>,
+    #[serde(default)]
+    mode: Option<Mode>,
+}
+
+#[derive(Serialize)]
+pub struct RoutesResponse {
+    // None until routes have been fetched, or always for a source without getroutes
+    fetched_at: Option<DateTime<Utc>>,
+    routes: Vec<RouteInfo>,
+}
+
+pub struct Routes {
+    overrides: HashMap<String, RouteOverride>,
+    known: Mutex<Known>,
+    // true once the first fetch has been tried
+    fetched: watch::Sender<bool>,
+}
+
+struct Known {
+    fetched_at: Option<DateTime<Utc>>,
+    // what the source said, before overrides
+    fetched: BTreeMap<String, RouteInfo>,
+    // with overrides applied
+    routes: BTreeMap<String, RouteInfo>,
+    // feed -> the routes it listed the last time it answered
+    feeds: HashMap<String, Vec<String>>,
+}
+
+impl Routes {
+    // `upstream` is None for sources without getroutes
+    pub fn spawn(
+        upstream: Option<Arc<Upstream>>,
+        feeds: &[FeedConfig],
+        overrides: HashMap<String, RouteOverride>,
+    ) -> Arc<Self> {
+        let routes = Arc::new(Routes {
+            known: Mutex::new(Known {
+                fetched_at: None,
+                fetched: BTreeMap::new(),
+                routes: merge(&BTreeMap::new(), &overrides),
+                feeds: HashMap::new(),
+            }),
+            overrides,
+            fetched: watch::channel(false).0,
+        });
+
+        if let Some(upstream) = upstream {
+            let background = routes.clone();
+            let feeds = bustime::feeds(feeds);
+            tokio::spawn(async move {
+                loop {
+                    let wait = if background.refresh(&upstream, &feeds).await {
+                        ROUTE_REFRESH_SECONDS
+                    } else {
+                        ROUTE_RETRY_SECONDS
+                    };
+                    sleep(Duration::from_secs(wait)).await;
+                }
+            });
+        }
+
+        routes
+    }
+
+    // asks every feed for its routes, true if they all answered
+    async fn refresh(&self, upstream: &Upstream, feeds: &[FeedConfig]) -> bool {
+        let results = join_all(
+            feeds
+                .iter()
+                .map(|feed| bustime::fetch_routes(upstream, feed)),
+        )
+        .await;
+
+        let mut known = self.known.lock().unwrap();
+        let mut complete = true;
+        let mut fetched = BTreeMap::new();
+        for (feed, result) in feeds.iter().zip(results) {
+            match result {
+                Ok(routes) => {
+                    known.feeds.insert(
+                        feed.name.clone(),
+                        routes.iter().map(|route| route.route.clone()).collect(),
+                    );
+                    for route in routes {
+                        // a route in several feeds keeps the first feed's details
+                        fetched.entry(route.route.clone()).or_insert(route);
+                    }
+                }
+                Err(e) => {
+                    println!("Fetching routes of {} failed: {:?}", feed.name, e);
+                    complete = false;
+                    // keep what this feed said last time
+                    for (id, route) in &known.fetched {
+                        if route.mode == feed.mode {
+                            fetched.entry(id.clone()).or_insert_with(|| route.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        known.routes = merge(&fetched, &self.overrides);
+        known.fetched = fetched;
+        known.fetched_at = Some(Utc::now());
+        self.fetched.send_replace(true);
+        complete
+    }
+
+    // waits for the first fetch to be tried, forever for a source without getroutes
+    pub async fn wait_for_fetch(&self) {
+        let mut fetched = self.fetched.subscribe();
+        let _ = fetched.wait_for(|fetched| *fetched).await;
+    }
+
+    // the routes `feed` listed, empty until it has answered
+    pub fn of_feed(&self, feed: &FeedConfig) -> Vec<String> {
+        let known = self.known.lock().unwrap();
+        known.feeds.get(&feed.name).cloned().unwrap_or_default()
+    }
+
+    // sets every route group's name and color
+    pub fn attach(&self, data: &mut FrontendResponse) {
+        let known = self.known.lock().unwrap();
+        for group in data.values_mut().flatten() {
+            if let Some(info) = known.routes.get(&group.route) {
+                group.route_name = info.name.clone();
+                group.color = info.color.clone();
+            }
+        }
+    }
+}
+
+// reads the overrides file, keyed by route
+pub fn load_overrides(path: &str) -> Result<HashMap<String, RouteOverride>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("could not read {}: {}", path, e))?;
+    toml::from_str(&text).map_err(|e| format!("could not parse {}: {}", path, e))
+}
+
+// the fetched routes with the overrides on top, including overridden routes the source
+// didn't mention
+fn merge(
+    fetched: &BTreeMap<String, RouteInfo>,
+    overrides: &HashMap<String, RouteOverride>,
+) -> BTreeMap<String, RouteInfo> {
+    let mut routes = fetched.clone();
+    for (id, local) in overrides {
+        let route = routes.entry(id.clone()).or_insert_with(|| RouteInfo {
+            route: id.clone(),
+            name: None,
+            color: None,
+            mode: Mode::default(),
+        });
+        if local.name.is_some() {
+            route.name = local.name.clone();
+        }
+        if local.color.is_some() {
+            route.color = local.color.clone();
+        }
+        if let Some(mode) = local.mode {
+            route.mode = mode;
+        }
+    }
+    routes
+}
+
+pub async fn get_routes(State(state): State<AppState>) -> Json<RoutesResponse> {
+    let known = state.routes.known.lock().unwrap();
+    Json(RoutesResponse {
+        fetched_at: known.fetched_at,
+        routes: known.routes.values().cloned().collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::{self, testing::arrival};
+
+    #[test]
+    fn overrides_take_precedence() {
+        let fetched = BTreeMap::from([(
+            "61C".to_string(),
+            RouteInfo {
+                route: "61C".to_string(),
+                name: Some("McKeesport - Homestead".to_string()),
+                color: Some("#cc3399".to_string()),
+                mode: Mode::Bus,
+            },
+        )]);
+        let overrides: HashMap<String, RouteOverride> = toml::from_str(
+            r##"
+            [61C]
+            color = "#2ecc71"
+
+            [RED]
+            name = "Red Line"
+            mode = "rail"
+            "##,
+        )
+        .unwrap();
+
+        let routes = Routes {
+            known: Mutex::new(Known {
+                fetched_at: None,
+                routes: merge(&fetched, &overrides),
+                fetched,
+                feeds: HashMap::new(),
+            }),
+            overrides,
+            fetched: watch::channel(false).0,
+        };
+        let mut data = source::group(vec![
+            arrival("7117", "61C", "McKeesport", 5),
+            arrival("7117", "58", "Greenfield", 5),
+        ]);
+        routes.attach(&mut data);
+
+        let group = |route: &str| data["7117"].iter().find(|g| g.route == route).unwrap();
+        assert_eq!(
+            group("61C").route_name.as_deref(),
+            Some("McKeesport - Homestead")
+        );
+        assert_eq!(group("61C").color.as_deref(), Some("#2ecc71"));
+        assert_eq!(group("58").color, None, "unknown routes are left alone");
+
+        let known = routes.known.lock().unwrap();
+        assert_eq!(known.routes["RED"].mode, Mode::Rail);
+    }
+}
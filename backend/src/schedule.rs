@@ -0,0 +1,339 @@
+// scheduled departures from the GTFS static timetable (see gtfs.rs)
+// the live feed knows better, so these only fill stops it has nothing for: every stop while
+// it is down, or single stops it returned no predictions for
+// they are marked `scheduled` so the sign can tell riders they aren't live
+//
+// the timetable also knows when each route stops for the night, so route groups whose next
+// bus is the last one are flagged, live or not
+
+use crate::FrontendResponse;
+use crate::config::StopConfig;
+use crate::gtfs::{GtfsStatic, Trip};
+use crate::local_time;
+use crate::source::{self, Arrival, ArrivalDetails, ArrivalKind};
+use chrono::{DateTime, Days, FixedOffset, NaiveDate, NaiveTime, TimeDelta, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+// how far ahead scheduled departures are shown
+const HORIZON_MINUTES: i64 = 60;
+
+// a live bus this far past a scheduled time is still taken to be that trip, not the next one
+const LATE_MINUTES: i64 = 5;
+
+// and even then, only if nothing follows it for at least this long, so routes that run
+// through the night never get a "last bus"
+const OVERNIGHT_GAP_MINUTES: i64 = 60;
+
+pub struct Schedule {
+    gtfs: Arc<GtfsStatic>,
+}
+
+// when one route's first and last trips leave a stop on the current service day
+#[derive(Serialize, Debug, Clone)]
+pub struct RouteServiceDay {
+    pub route: String,
+    pub first_departure: DateTime<FixedOffset>,
+    pub last_departure: DateTime<FixedOffset>,
+}
+
+impl Schedule {
+    pub fn new(gtfs: Arc<GtfsStatic>) -> Self {
+        Schedule { gtfs }
+    }
+
+    // adds scheduled departures for each of `stops` that `data` has no arrivals for
+    pub fn fill_gaps(&self, data: &mut FrontendResponse, stops: &[StopConfig], now: DateTime<Utc>) {
+        let missing = stops
+            .iter()
+            .filter(|stop| data.get(&stop.id).is_none_or(Vec::is_empty));
+        let arrivals = missing
+            .flat_map(|stop| self.departures(&stop.id, now))
+            .collect();
+        data.extend(source::group(arrivals));
+    }
+
+    // departures from `stop` in the next HORIZON_MINUTES
+    pub fn departures(&self, stop: &str, now: DateTime<Utc>) -> Vec<Arrival> {
+        let until = now + TimeDelta::minutes(HORIZON_MINUTES);
+        service_days(now)
+            .into_iter()
+            .flat_map(|date| self.departures_on(stop, date))
+            .filter(|(_, departs_at)| *departs_at >= now && *departs_at < until)
+            .map(|(trip, departs_at)| Arrival {
+                stop: stop.to_string(),
+                route: self.route_name(trip).to_string(),
+                destination: trip.headsign.clone(),
+                // no particular bus is assigned yet
+                bus_id: String::new(),
+                predicted_at: now.with_timezone(&local_time::TIMEZONE).fixed_offset(),
+                arrival_at: departs_at.fixed_offset(),
+                capacity: String::new(),
+                kind: ArrivalKind::Scheduled,
+                mode: self.gtfs.route_mode(&trip.route_id),
+                details: ArrivalDetails::default(),
+            })
+            .collect()
+    }
+
+    // sets `last_bus` on every route group whose next bus is the route's last of the night
+    pub fn mark_last_buses(&self, data: &mut FrontendResponse, now: DateTime<Utc>) {
+        for (stop, groups) in data.iter_mut() {
+            for group in groups.iter_mut() {
+                if let Some(next) = group.arrivals.first() {
+                    group.last_bus = self.is_last_bus(stop, &group.route, next.arrival_at, now);
+                }
+            }
+        }
+    }
+
+    // every route's first and last departure from `stop` on the service day under way at `now`
+    // (the previous one, until its last trip has left), in route order
+    pub fn service_day(&self, stop: &str, now: DateTime<Utc>) -> Vec<RouteServiceDay> {
+        let [yesterday, today, _] = service_days(now);
+        let spans = |date| {
+            let mut spans: BTreeMap<&str, (DateTime<Tz>, DateTime<Tz>)> = BTreeMap::new();
+            for (trip, at) in self.departures_on(stop, date) {
+                spans
+                    .entry(self.route_name(trip))
+                    .and_modify(|(first, last)| {
+                        *first = (*first).min(at);
+                        *last = (*last).max(at);
+                    })
+                    .or_insert((at, at));
+            }
+            spans
+        };
+
+        let late = now - TimeDelta::minutes(LATE_MINUTES);
+        let mut routes = spans(today);
+        for (route, span) in spans(yesterday) {
+            if span.1 >= late {
+                routes.insert(route, span);
+            }
+        }
+
+        routes
+            .into_iter()
+            .map(|(route, (first, last))| RouteServiceDay {
+                route: route.to_string(),
+                first_departure: first.fixed_offset(),
+                last_departure: last.fixed_offset(),
+            })
+            .collect()
+    }
+
+    // the bus due at `next` is no earlier than `route`'s last trip from `stop` on the current
+    // service day, and no later trip leaves soon after it
+    // buses running ahead of schedule aren't recognized; they are rare and only miss the flag
+    fn is_last_bus(
+        &self,
+        stop: &str,
+        route: &str,
+        next: DateTime<FixedOffset>,
+        now: DateTime<Utc>,
+    ) -> bool {
+        // routes the timetable doesn't know can't be judged
+        let last_trip = self
+            .service_day(stop, now)
+            .into_iter()
+            .find(|day| day.route == route)
+            .is_some_and(|day| next >= day.last_departure);
+        if !last_trip {
+            return false;
+        }
+
+        let after = next + TimeDelta::minutes(LATE_MINUTES);
+        let until = next + TimeDelta::minutes(OVERNIGHT_GAP_MINUTES);
+        !service_days(now)
+            .into_iter()
+            .flat_map(|date| self.departures_on(stop, date))
+            .filter(|(trip, _)| self.route_name(trip) == route)
+            .any(|(_, at)| at > after && at <= until)
+    }
+
+    // every trip leaving `stop` on the service day `date`, and when
+    fn departures_on(
+        &self,
+        stop: &str,
+        date: NaiveDate,
+    ) -> impl Iterator<Item = (&Trip, DateTime<Tz>)> {
+        // GTFS times count from noon minus 12h, which is midnight except on DST change days
+        let start = local_time::resolve(date.and_time(NaiveTime::from_hms_opt(12, 0, 0).unwrap()))
+            - TimeDelta::hours(12);
+
+        self.gtfs
+            .stop_times
+            .get(stop)
+            .into_iter()
+            .flatten()
+            .filter_map(move |stop_time| {
+                let trip = self.gtfs.trips.get(&stop_time.trip_id)?;
+                self.gtfs
+                    .runs_on(&trip.service_id, date)
+                    .then(|| (trip, start + TimeDelta::seconds(stop_time.departure.into())))
+            })
+    }
+
+    // the name live feeds use for the trip's route
+    fn route_name<'a>(&'a self, trip: &'a Trip) -> &'a str {
+        self.gtfs.route_name(&trip.route_id)
+    }
+}
+
+// yesterday's service day runs past midnight, and tomorrow's may start within the horizon
+fn service_days(now: DateTime<Utc>) -> [NaiveDate; 3] {
+    let today = now.with_timezone(&local_time::TIMEZONE).date_naive();
+    [today - Days::new(1), today, today + Days::new(1)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gtfs::testing::timetable;
+    use crate::source::testing::arrival;
+    use chrono::TimeZone;
+
+    fn stop(id: &str) -> StopConfig {
+        StopConfig {
+            id: id.to_string(),
+            label: None,
+            walk_time: None,
+            side: None,
+        }
+    }
+
+    // local time on 2026-10-18, or the early hours of the 19th for hours past 24
+    fn local(hour: u32, minute: u32) -> DateTime<Utc> {
+        local_time::TIMEZONE
+            .with_ymd_and_hms(2026, 10, 18 + hour / 24, hour % 24, minute, 0)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn departures_span_service_days() {
+        let schedule = Schedule::new(Arc::new(timetable(
+            "7117",
+            &[
+                ("left", "61C", "McKeesport", "23:20:00"),
+                ("today", "61C", "McKeesport", "23:45:00"),
+                ("after-midnight", "61C", "McKeesport", "24:10:00"),
+                ("tomorrow", "61D", "Murray", "00:20:00"),
+                ("too-late", "61C", "McKeesport", "25:00:00"),
+            ],
+        )));
+        let now = local(23, 30);
+
+        let mut data = source::group(vec![arrival("4407", "67", "Downtown", 5)]);
+        schedule.fill_gaps(&mut data, &[stop("7117"), stop("4407")], now);
+
+        assert_eq!(data["4407"][0].arrivals[0].kind, ArrivalKind::Realtime);
+        assert_eq!(
+            data["4407"][0].arrivals.len(),
+            1,
+            "live stops are left alone"
+        );
+
+        let groups = &data["7117"];
+        let mckeesport = groups.iter().find(|g| g.route == "61C").unwrap();
+        let seconds: Vec<i64> = mckeesport.arrivals.iter().map(|a| a.seconds).collect();
+        assert_eq!(seconds, [15 * 60, 40 * 60]);
+        assert!(
+            mckeesport
+                .arrivals
+                .iter()
+                .all(|a| a.kind == ArrivalKind::Scheduled)
+        );
+
+        let murray = groups.iter().find(|g| g.route == "61D").unwrap();
+        assert_eq!(murray.arrivals[0].seconds, 50 * 60);
+    }
+
+    #[test]
+    fn flags_the_last_bus_of_the_night() {
+        let schedule = Schedule::new(Arc::new(timetable(
+            "7117",
+            &[
+                ("61C-1", "61C", "McKeesport", "22:30:00"),
+                ("61C-2", "61C", "McKeesport", "23:45:00"),
+                ("61C-3", "61C", "McKeesport", "24:10:00"),
+                ("61D-1", "61D", "Murray", "05:00:00"),
+                ("61D-2", "61D", "Murray", "23:40:00"),
+                // runs all night, the next day's trips pick up where the last day's stop
+                ("owl-1", "P1", "Airport", "23:50:00"),
+                ("owl-2", "P1", "Airport", "24:40:00"),
+                ("owl-3", "P1", "Airport", "01:20:00"),
+            ],
+        )));
+        let now = local(23, 30);
+        let due = |route: &str, hour, minute| Arrival {
+            arrival_at: local(hour, minute).fixed_offset(),
+            ..arrival("7117", route, "", 0)
+        };
+
+        let mut data = source::group(vec![
+            due("61C", 23, 47),
+            due("61D", 23, 42),
+            due("P1", 24, 40),
+        ]);
+        schedule.mark_last_buses(&mut data, now);
+        let last_bus = |data: &FrontendResponse, route: &str| {
+            data["7117"]
+                .iter()
+                .find(|g| g.route == route)
+                .unwrap()
+                .last_bus
+        };
+        assert!(!last_bus(&data, "61C"), "another 61C at 00:10");
+        assert!(last_bus(&data, "61D"));
+        assert!(!last_bus(&data, "P1"));
+
+        // the 00:10, running a few minutes late
+        let mut data = source::group(vec![due("61C", 24, 13)]);
+        schedule.mark_last_buses(&mut data, now);
+        assert!(last_bus(&data, "61C"));
+
+        let day = schedule.service_day("7117", now);
+        let routes: Vec<&str> = day.iter().map(|d| d.route.as_str()).collect();
+        assert_eq!(routes, ["61C", "61D", "P1"]);
+        assert_eq!(day[0].first_departure, local(22, 30));
+        assert_eq!(day[0].last_departure, local(24, 10));
+
+        // just after midnight the 61C is still on the 18th's service day, the 61D isn't
+        let day = schedule.service_day("7117", local(24, 5));
+        assert_eq!(day[0].first_departure, local(22, 30));
+        assert_eq!(day[1].first_departure, local(29, 0));
+    }
+
+    #[test]
+    fn sparse_routes_only_get_a_last_bus_at_night() {
+        let schedule = Schedule::new(Arc::new(timetable(
+            "7117",
+            &[
+                ("28X-1", "28X", "Airport", "09:00:00"),
+                ("28X-2", "28X", "Airport", "10:30:00"),
+                ("28X-3", "28X", "Airport", "12:00:00"),
+                ("28X-4", "28X", "Airport", "13:30:00"),
+                ("28X-5", "28X", "Airport", "21:00:00"),
+            ],
+        )));
+        let due = |hour, minute| Arrival {
+            arrival_at: local(hour, minute).fixed_offset(),
+            ..arrival("7117", "28X", "Airport", 0)
+        };
+
+        let mut data = source::group(vec![due(12, 2)]);
+        schedule.mark_last_buses(&mut data, local(11, 50));
+        assert!(
+            !data["7117"][0].last_bus,
+            "90 minutes to the next one at midday"
+        );
+
+        let mut data = source::group(vec![due(21, 3)]);
+        schedule.mark_last_buses(&mut data, local(20, 50));
+        assert!(data["7117"][0].last_bus);
+    }
+}
@@ -0,0 +1,355 @@
+// service hours
+// no buses run on the sign's routes overnight, so there is nothing worth polling PRT for;
+// the optional [service] section of the sign config gives the weekly schedule (plus holiday
+// overrides) in Pittsburgh local time, and outside it the poller slows down or stops and
+// the API says when service resumes
+//
+// [service]
+// weekday = "05:00-01:00"         # ending at or before the start runs past midnight
+// saturday = "weekday"            # same hours as another day type
+// sunday = "closed"
+// off_hours_poll_seconds = 900    # omit to stop polling entirely outside service hours
+//
+// [service.holidays]
+// "2026-12-25" = "sunday"
+
+use crate::local_time::{self, TIMEZONE};
+use chrono::{
+    DateTime, Datelike, Days, FixedOffset, NaiveDate, NaiveTime, TimeDelta, Utc, Weekday,
+};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, time::Duration};
+
+// how far ahead to look for the next service day (long holiday closures included)
+const LOOKAHEAD_DAYS: u64 = 14;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ServiceHours {
+    weekday: Hours,
+    saturday: Hours,
+    sunday: Hours,
+    // dates with hours other than their weekday's, usually "sunday" or "closed"
+    #[serde(default)]
+    holidays: HashMap<NaiveDate, Hours>,
+    #[serde(default)]
+    off_hours_poll_seconds: Option<u64>,
+}
+
+// hours of one service day, written "HH:MM-HH:MM", "closed" or the name of a day type
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(try_from = "String")]
+enum Hours {
+    Closed,
+    // start and end local time; the same time for both means around the clock
+    Open(NaiveTime, NaiveTime),
+    Like(DayType),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum DayType {
+    Weekday,
+    Saturday,
+    Sunday,
+}
+
+// whether buses are running right now, passed on to clients as-is
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ServiceStatus {
+    pub in_service: bool,
+    // when service next starts, unless it's running now or nothing is scheduled for weeks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resumes_at: Option<DateTime<FixedOffset>>,
+    // ready for the sign to show, e.g. "No service until 05:00"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl ServiceStatus {
+    pub fn running() -> Self {
+        ServiceStatus {
+            in_service: true,
+            resumes_at: None,
+            message: None,
+        }
+    }
+
+    fn suspended(now: DateTime<Tz>, resumes_at: Option<DateTime<Tz>>) -> Self {
+        let message = match resumes_at {
+            // a bare time is only unambiguous within the next day
+            Some(at) if at - now < TimeDelta::hours(24) => {
+                format!("No service until {}", at.format("%H:%M"))
+            }
+            Some(at) => format!("No service until {}", at.format("%a %H:%M")),
+            None => "No service scheduled".to_string(),
+        };
+
+        ServiceStatus {
+            in_service: false,
+            resumes_at: resumes_at.map(|at| at.fixed_offset()),
+            message: Some(message),
+        }
+    }
+}
+
+impl TryFrom<String> for Hours {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, String> {
+        match text.trim() {
+            "closed" => Ok(Hours::Closed),
+            "weekday" => Ok(Hours::Like(DayType::Weekday)),
+            "saturday" => Ok(Hours::Like(DayType::Saturday)),
+            "sunday" => Ok(Hours::Like(DayType::Sunday)),
+            hours => {
+                let (start, end) = hours.split_once('-').ok_or_else(|| {
+                    format!(
+                        "expected \"HH:MM-HH:MM\", \"closed\" or a day type, got {:?}",
+                        text
+                    )
+                })?;
+                let parse = |time: &str| {
+                    NaiveTime::parse_from_str(time.trim(), "%H:%M")
+                        .map_err(|_| format!("invalid time {:?} in {:?}", time, text))
+                };
+                Ok(Hours::Open(parse(start)?, parse(end)?))
+            }
+        }
+    }
+}
+
+impl ServiceHours {
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, hours) in [
+            ("weekday", self.weekday),
+            ("saturday", self.saturday),
+            ("sunday", self.sunday),
+        ] {
+            if let Hours::Like(day) = hours
+                && let Hours::Like(_) = self.regular(day)
+            {
+                return Err(format!(
+                    "service.{} must refer to a day type with its own hours",
+                    name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // how often to poll outside service hours, None to not poll at all
+    pub fn off_hours_poll(&self) -> Option<Duration> {
+        self.off_hours_poll_seconds.map(Duration::from_secs)
+    }
+
+    pub fn status(&self, now: DateTime<Utc>) -> ServiceStatus {
+        let now = now.with_timezone(&TIMEZONE);
+        let today = now.date_naive();
+
+        // yesterday's service may still be running past midnight
+        for date in [today.pred_opt(), Some(today)].into_iter().flatten() {
+            if let Some((start, end)) = self.window(date)
+                && start <= now
+                && now < end
+            {
+                return ServiceStatus::running();
+            }
+        }
+
+        let resumes_at = (0..=LOOKAHEAD_DAYS)
+            .filter_map(|days| today.checked_add_days(Days::new(days)))
+            .filter_map(|date| self.window(date))
+            .map(|(start, _)| start)
+            .find(|start| *start > now);
+
+        ServiceStatus::suspended(now, resumes_at)
+    }
+
+    // start and end of the service day that begins on `date`
+    fn window(&self, date: NaiveDate) -> Option<(DateTime<Tz>, DateTime<Tz>)> {
+        let regular = match date.weekday() {
+            Weekday::Sat => self.saturday,
+            Weekday::Sun => self.sunday,
+            _ => self.weekday,
+        };
+        let mut hours = self.holidays.get(&date).copied().unwrap_or(regular);
+        // validate() makes sure this ends within two steps
+        while let Hours::Like(day) = hours {
+            hours = self.regular(day);
+        }
+
+        match hours {
+            Hours::Open(start, end) => {
+                let end_date = if end <= start { date.succ_opt()? } else { date };
+                Some((
+                    local_time::resolve(date.and_time(start)),
+                    local_time::resolve(end_date.and_time(end)),
+                ))
+            }
+            Hours::Closed | Hours::Like(_) => None,
+        }
+    }
+
+    fn regular(&self, day: DayType) -> Hours {
+        match day {
+            DayType::Weekday => self.weekday,
+            DayType::Saturday => self.saturday,
+            DayType::Sunday => self.sunday,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(toml: &str) -> ServiceHours {
+        let hours: ServiceHours = toml::from_str(toml).unwrap();
+        hours.validate().unwrap();
+        hours
+    }
+
+    // local times are written with their offset, so the hours around DST changes are exact
+    fn at(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_str(text, "%Y-%m-%d %H:%M %z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn resumes_at(status: &ServiceStatus) -> Option<String> {
+        status
+            .resumes_at
+            .map(|at| at.format("%Y-%m-%d %H:%M %z").to_string())
+    }
+
+    const WEEK: &str = r#"
+        weekday = "05:00-01:00"
+        saturday = "06:00-01:30"
+        sunday = "07:00-23:00"
+    "#;
+
+    #[test]
+    fn windows_run_past_midnight() {
+        let hours = parse(WEEK);
+        // Monday's service, still running early on Tuesday
+        assert!(hours.status(at("2026-10-20 00:30 -0400")).in_service);
+
+        let status = hours.status(at("2026-10-20 01:00 -0400"));
+        assert!(!status.in_service);
+        assert_eq!(
+            resumes_at(&status).as_deref(),
+            Some("2026-10-20 05:00 -0400")
+        );
+        assert_eq!(status.message.as_deref(), Some("No service until 05:00"));
+
+        assert!(hours.status(at("2026-10-20 05:00 -0400")).in_service);
+    }
+
+    #[test]
+    fn holidays_close_service() {
+        let hours = parse(&format!(
+            "{}\n[holidays]\n\"2026-12-25\" = \"closed\"",
+            WEEK
+        ));
+        // Christmas Eve's service still runs into Christmas morning
+        assert!(hours.status(at("2026-12-25 00:30 -0500")).in_service);
+
+        let status = hours.status(at("2026-12-25 12:00 -0500"));
+        assert!(!status.in_service);
+        assert_eq!(
+            resumes_at(&status).as_deref(),
+            Some("2026-12-26 06:00 -0500")
+        );
+    }
+
+    #[test]
+    fn holidays_change_hours() {
+        let hours = parse(&format!(
+            "{}\n[holidays]\n\"2026-11-26\" = \"sunday\"\n\"2026-11-27\" = \"08:00-20:00\"",
+            WEEK
+        ));
+        // Thanksgiving runs Sunday hours
+        let status = hours.status(at("2026-11-26 06:00 -0500"));
+        assert!(!status.in_service);
+        assert_eq!(
+            resumes_at(&status).as_deref(),
+            Some("2026-11-26 07:00 -0500")
+        );
+        assert!(!hours.status(at("2026-11-26 23:30 -0500")).in_service);
+
+        // and the day after its own
+        let status = hours.status(at("2026-11-27 05:30 -0500"));
+        assert!(!status.in_service);
+        assert_eq!(
+            resumes_at(&status).as_deref(),
+            Some("2026-11-27 08:00 -0500")
+        );
+        assert!(!hours.status(at("2026-11-27 20:30 -0500")).in_service);
+    }
+
+    #[test]
+    fn looks_ahead_across_a_closed_weekend() {
+        let hours = parse(
+            r#"
+            weekday = "05:00-01:00"
+            saturday = "closed"
+            sunday = "closed"
+            "#,
+        );
+        let status = hours.status(at("2026-10-24 02:00 -0400"));
+        assert!(!status.in_service);
+        assert_eq!(
+            resumes_at(&status).as_deref(),
+            Some("2026-10-26 05:00 -0400")
+        );
+        assert_eq!(
+            status.message.as_deref(),
+            Some("No service until Mon 05:00")
+        );
+
+        let closed = parse(
+            r#"
+            weekday = "closed"
+            saturday = "closed"
+            sunday = "closed"
+            "#,
+        );
+        let status = closed.status(at("2026-10-24 02:00 -0400"));
+        assert_eq!(status.resumes_at, None);
+        assert_eq!(status.message.as_deref(), Some("No service scheduled"));
+    }
+
+    #[test]
+    fn starts_after_clocks_spring_forward() {
+        // 02:30 doesn't happen on 2026-03-08, so service starts at 03:30 EDT instead
+        let hours = parse(
+            r#"
+            weekday = "05:00-01:00"
+            saturday = "06:00-01:00"
+            sunday = "02:30-23:00"
+            "#,
+        );
+        let status = hours.status(at("2026-03-08 01:59 -0500"));
+        assert!(!status.in_service);
+        assert_eq!(
+            resumes_at(&status).as_deref(),
+            Some("2026-03-08 03:30 -0400")
+        );
+        assert!(!hours.status(at("2026-03-08 03:15 -0400")).in_service);
+        assert!(hours.status(at("2026-03-08 03:30 -0400")).in_service);
+    }
+
+    #[test]
+    fn ends_on_the_first_pass_when_clocks_fall_back() {
+        // Saturday's service ends at 01:30, which happens twice on 2026-11-01
+        let hours = parse(WEEK);
+        assert!(hours.status(at("2026-11-01 01:15 -0400")).in_service);
+        assert!(!hours.status(at("2026-11-01 01:45 -0400")).in_service);
+        let status = hours.status(at("2026-11-01 01:15 -0500"));
+        assert!(!status.in_service, "the second 01:15 is after the end");
+        assert_eq!(
+            resumes_at(&status).as_deref(),
+            Some("2026-11-01 07:00 -0500")
+        );
+    }
+}
@@ -0,0 +1,153 @@
+// per-sign views of the shared prediction cache
+
+use crate::config::{RouteOrder, SignProfile};
+use crate::{FrontendResponse, RouteGroup};
+
+// keeps only the sign's stops and routes, ordered the way the sign asks for
+pub fn view(sign: &SignProfile, data: &FrontendResponse) -> FrontendResponse {
+    let mut output = FrontendResponse::new();
+
+    for stop_id in &sign.stops {
+        let mut groups: Vec<RouteGroup> = data
+            .get(stop_id)
+            .map(|groups| {
+                groups
+                    .iter()
+                    .filter(|g| sign.shows_route(&g.route))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        sort_groups(sign, &mut groups);
+        output.insert(stop_id.clone(), groups);
+    }
+
+    output
+}
+
+fn sort_groups(sign: &SignProfile, groups: &mut [RouteGroup]) {
+    match sign.order {
+        RouteOrder::Arrival => groups.sort_by_key(next_arrival),
+        RouteOrder::Route => {
+            groups.sort_by(|a, b| (&a.route, &a.destination).cmp(&(&b.route, &b.destination)))
+        }
+        RouteOrder::Listed => groups.sort_by_key(|g| {
+            let rank = sign.routes.iter().position(|r| *r == g.route);
+            (rank.unwrap_or(usize::MAX), next_arrival(g))
+        }),
+    }
+}
+
+// groups without arrivals sort last
+fn next_arrival(group: &RouteGroup) -> i64 {
+    group
+        .arrivals
+        .first()
+        .map(|a| a.seconds)
+        .unwrap_or(i64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::{self, testing::arrival};
+
+    fn sign(routes: &[&str], exclude_routes: &[&str], order: RouteOrder) -> SignProfile {
+        SignProfile {
+            id: "lobby".to_string(),
+            stops: vec!["4407".to_string(), "7117".to_string()],
+            routes: routes.iter().map(|r| r.to_string()).collect(),
+            exclude_routes: exclude_routes.iter().map(|r| r.to_string()).collect(),
+            order,
+        }
+    }
+
+    fn data() -> FrontendResponse {
+        source::group(vec![
+            arrival("7117", "67", "Monroeville", 9),
+            arrival("7117", "61C", "McKeesport", 4),
+            arrival("7117", "61C", "Homestead", 12),
+            arrival("7117", "58", "Greenfield", 6),
+            arrival("4407", "61C", "Downtown", 2),
+            arrival("9999", "71B", "Highland Park", 1),
+        ])
+    }
+
+    // "route destination" of each group at the stop, in order
+    fn shown(view: &FrontendResponse, stop: &str) -> Vec<String> {
+        view[stop]
+            .iter()
+            .map(|g| format!("{} {}", g.route, g.destination))
+            .collect()
+    }
+
+    #[test]
+    fn shows_only_the_signs_stops() {
+        let view = view(&sign(&[], &[], RouteOrder::Arrival), &data());
+        let mut stops: Vec<&String> = view.keys().collect();
+        stops.sort();
+        assert_eq!(stops, ["4407", "7117"]);
+        assert_eq!(shown(&view, "4407"), ["61C Downtown"]);
+    }
+
+    #[test]
+    fn filters_included_and_excluded_routes() {
+        let included = view(&sign(&["61C", "58"], &[], RouteOrder::Arrival), &data());
+        assert_eq!(
+            shown(&included, "7117"),
+            ["61C McKeesport", "58 Greenfield", "61C Homestead"]
+        );
+
+        let excluded = view(&sign(&[], &["61C"], RouteOrder::Arrival), &data());
+        assert_eq!(
+            shown(&excluded, "7117"),
+            ["58 Greenfield", "67 Monroeville"]
+        );
+        assert!(shown(&excluded, "4407").is_empty());
+
+        // exclusion wins over inclusion
+        let both = view(&sign(&["61C", "58"], &["58"], RouteOrder::Arrival), &data());
+        assert_eq!(shown(&both, "7117"), ["61C McKeesport", "61C Homestead"]);
+    }
+
+    #[test]
+    fn orders_groups_each_way() {
+        let mut data = data();
+        // a group whose buses have all left sorts last by arrival
+        data.get_mut("7117").unwrap()[0].arrivals.clear();
+
+        let by_arrival = view(&sign(&[], &[], RouteOrder::Arrival), &data);
+        assert_eq!(
+            shown(&by_arrival, "7117"),
+            [
+                "61C McKeesport",
+                "58 Greenfield",
+                "61C Homestead",
+                "67 Monroeville"
+            ]
+        );
+
+        let by_route = view(&sign(&[], &[], RouteOrder::Route), &data);
+        assert_eq!(
+            shown(&by_route, "7117"),
+            [
+                "58 Greenfield",
+                "61C Homestead",
+                "61C McKeesport",
+                "67 Monroeville"
+            ]
+        );
+
+        let listed = view(&sign(&["67", "61C", "58"], &[], RouteOrder::Listed), &data);
+        assert_eq!(
+            shown(&listed, "7117"),
+            [
+                "67 Monroeville",
+                "61C McKeesport",
+                "61C Homestead",
+                "58 Greenfield"
+            ]
+        );
+    }
+}
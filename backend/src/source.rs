@@ -0,0 +1,256 @@
+// where predictions come from
+// a PredictionSource fetches arrivals for the configured stops from one feed and hands them
+// back normalized; grouping, caching and countdowns are the same for every source
+// (grouping here, the rest in poller.rs and current_predictions)
+
+use crate::budget::IDLE_INTERVAL;
+use crate::config::StopConfig;
+use crate::{
+    AppError, BusArrival, CACHE_DURATION_SECONDS, FrontendResponse, PrtMessage, RouteGroup,
+    StopFailure,
+};
+use chrono::{DateTime, FixedOffset};
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// one predicted arrival of one bus at one stop
+#[derive(Debug, Clone)]
+pub struct Arrival {
+    pub stop: String,
+    pub route: String,
+    pub destination: String,
+    pub bus_id: String,
+    // when the feed made the prediction; countdowns run from here
+    pub predicted_at: DateTime<FixedOffset>,
+    pub arrival_at: DateTime<FixedOffset>,
+    pub capacity: String,
+    pub kind: ArrivalKind,
+    pub mode: Mode,
+    pub details: ArrivalDetails,
+}
+
+// what BusTime says about a prediction beyond the above; other sources fill in what they can
+#[derive(Debug, Clone, Default)]
+pub struct ArrivalDetails {
+    // "INBOUND" or "OUTBOUND"
+    pub direction: Option<String>,
+    // the feed's name for the stop
+    pub stop_name: Option<String>,
+    // how far the bus still has to go, in feet
+    pub distance_feet: Option<u32>,
+    // the feed considers the bus late
+    pub delayed: bool,
+    // BusTime's own countdown as of the fetch: minutes, "DUE" or "DLY"
+    pub countdown: Option<String>,
+    pub trip_id: Option<String>,
+    pub block_id: Option<String>,
+    // BusTime's dynamic action type, 0 for a normal trip (1 canceled, 4 expressed, ...)
+    pub dynamic_action: Option<u8>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ArrivalKind {
+    // predicted by a live feed
+    Realtime,
+    // from the timetable, when the live feed had nothing (see schedule.rs)
+    Scheduled,
+}
+
+// what kind of vehicle serves a route, so the sign can style them differently
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    #[default]
+    Bus,
+    // the T, PRT's light rail
+    Rail,
+    Incline,
+}
+
+// everything one fetch found out
+#[derive(Default)]
+pub struct SourceUpdate {
+    pub arrivals: Vec<Arrival>,
+    // problems the feed reported alongside the data
+    pub messages: Vec<PrtMessage>,
+    // stops that couldn't be fetched while the rest could
+    pub failures: Vec<StopFailure>,
+}
+
+pub trait PredictionSource: Send + Sync {
+    // reported as `source` in /v2 responses
+    fn name(&self) -> &'static str;
+
+    // arrivals at every stop in `stops`; Err only if nothing at all could be fetched
+    fn fetch<'a>(
+        &'a self,
+        stops: &'a [StopConfig],
+    ) -> BoxFuture<'a, Result<SourceUpdate, AppError>>;
+
+    // time until the next poll, `idle` if nobody is watching any sign
+    fn poll_interval(&self, idle: bool, _stops: usize) -> Duration {
+        if idle {
+            IDLE_INTERVAL
+        } else {
+            Duration::from_secs(CACHE_DURATION_SECONDS)
+        }
+    }
+}
+
+// arrivals grouped by stop, then by route, destination and mode, soonest first within each group
+pub fn group(arrivals: Vec<Arrival>) -> FrontendResponse {
+    let mut output = FrontendResponse::new();
+
+    for a in arrivals {
+        let stop_list = output.entry(a.stop).or_default();
+
+        // data for each bus
+        let arrival = BusArrival {
+            bus_id: a.bus_id,
+            seconds: a
+                .arrival_at
+                .signed_duration_since(a.predicted_at)
+                .num_seconds(),
+            arrival_at: a.arrival_at,
+            capacity: a.capacity,
+            kind: a.kind,
+            distance_feet: a.details.distance_feet,
+            delayed: a.details.delayed,
+            countdown: a.details.countdown,
+            trip_id: a.details.trip_id,
+            block_id: a.details.block_id,
+            dynamic_action: a.details.dynamic_action,
+        };
+
+        // if stop data already exists, update it; otherwise, make new
+        if let Some(group) = stop_list
+            .iter_mut()
+            .find(|g| g.route == a.route && g.destination == a.destination && g.mode == a.mode)
+        {
+            group.arrivals.push(arrival);
+            group.arrivals.sort_by_key(|b| b.seconds);
+            group.direction = group.direction.take().or(a.details.direction);
+            group.stop_name = group.stop_name.take().or(a.details.stop_name);
+        } else {
+            stop_list.push(RouteGroup {
+                route: a.route,
+                route_name: None,
+                color: None,
+                destination: a.destination,
+                arrivals: vec![arrival],
+                last_bus: false,
+                mode: a.mode,
+                alerts: Vec::new(),
+                direction: a.details.direction,
+                stop_name: a.details.stop_name,
+            });
+        }
+    }
+
+    output
+}
+
+// a fixed set of arrivals, for testing everything downstream of a feed
+#[cfg(test)]
+pub mod testing {
+    use super::*;
+    use chrono::{TimeDelta, Utc};
+    use std::sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    pub struct InMemorySource {
+        // what every fetch returns until replaced
+        pub result: Mutex<Result<Vec<Arrival>, AppError>>,
+        // stops reported as failed alongside a successful result
+        pub failures: Mutex<Vec<StopFailure>>,
+        fetches: AtomicUsize,
+    }
+
+    impl InMemorySource {
+        pub fn new(arrivals: Vec<Arrival>) -> Self {
+            InMemorySource {
+                result: Mutex::new(Ok(arrivals)),
+                failures: Mutex::new(Vec::new()),
+                fetches: AtomicUsize::new(0),
+            }
+        }
+
+        pub fn fetch_count(&self) -> usize {
+            self.fetches.load(Ordering::SeqCst)
+        }
+    }
+
+    impl PredictionSource for InMemorySource {
+        fn name(&self) -> &'static str {
+            "memory"
+        }
+
+        fn fetch<'a>(
+            &'a self,
+            stops: &'a [StopConfig],
+        ) -> BoxFuture<'a, Result<SourceUpdate, AppError>> {
+            Box::pin(async move {
+                self.fetches.fetch_add(1, Ordering::SeqCst);
+                // long enough for concurrent callers to pile up behind this fetch
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                let arrivals = self.result.lock().unwrap().clone()?;
+                Ok(SourceUpdate {
+                    arrivals: arrivals
+                        .into_iter()
+                        .filter(|a| stops.iter().any(|s| s.id == a.stop))
+                        .collect(),
+                    failures: self.failures.lock().unwrap().clone(),
+                    ..SourceUpdate::default()
+                })
+            })
+        }
+    }
+
+    // a bus predicted just now to reach `stop` in `minutes`
+    pub fn arrival(stop: &str, route: &str, destination: &str, minutes: i64) -> Arrival {
+        let now = Utc::now().fixed_offset();
+        Arrival {
+            stop: stop.to_string(),
+            route: route.to_string(),
+            destination: destination.to_string(),
+            bus_id: format!("{}-{}", route, minutes),
+            predicted_at: now,
+            arrival_at: now + TimeDelta::minutes(minutes),
+            capacity: String::new(),
+            kind: ArrivalKind::Realtime,
+            mode: Mode::Bus,
+            details: ArrivalDetails::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::arrival;
+    use super::*;
+
+    #[test]
+    fn groups_by_stop_route_and_destination() {
+        let data = group(vec![
+            arrival("7117", "61C", "McKeesport", 9),
+            arrival("7117", "61C", "McKeesport", 2),
+            arrival("7117", "61C", "Downtown", 5),
+            arrival("4407", "67", "Downtown", 14),
+        ]);
+
+        assert_eq!(data.len(), 2);
+        let uc = &data["7117"];
+        assert_eq!(uc.len(), 2);
+
+        let mckeesport = uc.iter().find(|g| g.destination == "McKeesport").unwrap();
+        let seconds: Vec<i64> = mckeesport.arrivals.iter().map(|a| a.seconds).collect();
+        assert_eq!(seconds, [120, 540], "soonest arrival first");
+
+        assert_eq!(data["4407"][0].arrivals[0].seconds, 14 * 60);
+    }
+}
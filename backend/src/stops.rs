@@ -0,0 +1,377 @@
+// /stops and /signs/{sign_id}/stops: the configured stops, each with PRT's name for it, the
+// direction its buses leave in and where it is, so signs can draw their headers from data
+// the local label, walk time and side from config.rs are passed through alongside
+//
+// names and places come from BusTime's getstops, directions from getdirections and getstops
+// for every route each feed listed (see routes.rs), which also tells which routes serve the
+// stops (vehicles.rs and alerts.rs ask about those)
+// a background task scans them all at startup and every STOP_REFRESH_SECONDS; that is a few
+// calls per route, a few hundred out of the daily budget, so lookups that fail are retried
+// on their own, STOP_RETRY_SECONDS later and then twice as long each time, while what the
+// rest found is used straight away
+// a GTFS-rt source has only the local metadata
+
+use crate::config::{Config, FeedConfig, StopConfig};
+use crate::routes::Routes;
+use crate::upstream::Upstream;
+use crate::{AppError, AppState, FrontendResponse, bustime};
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::{Instant, sleep_until};
+
+// time between scans
+const STOP_REFRESH_SECONDS: u64 = 24 * 60 * 60;
+
+// before the first retry of the lookups that failed
+const STOP_RETRY_SECONDS: u64 = 10 * 60;
+
+// one stop as BusTime knows it
+#[derive(Debug, Clone, PartialEq)]
+pub struct StopDetails {
+    pub id: String,
+    pub name: String,
+    pub lat: f64,
+    pub lon: f64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct StopInfo {
+    #[serde(flatten)]
+    config: StopConfig,
+    // PRT's name ("Forbes Ave at Morewood Ave (Carnegie Mellon)")
+    name: Option<String>,
+    // "INBOUND" or "OUTBOUND", if every route at the stop serves it the same way
+    direction: Option<String>,
+    lat: Option<f64>,
+    lon: Option<f64>,
+}
+
+pub struct Stops {
+    config: Arc<Config>,
+    known: Mutex<Known>,
+}
+
+#[derive(Default)]
+struct Known {
+    details: HashMap<String, StopDetails>,
+    // feed -> route -> each configured stop it serves, with the direction it serves it in
+    served: HashMap<String, BTreeMap<String, Vec<(String, String)>>>,
+    // every route has been looked up; until then a stop could seem to have one direction
+    // when it has two
+    complete: bool,
+}
+
+// one piece of a scan, by index into the feeds
+#[derive(Debug, Clone, PartialEq)]
+enum Lookup {
+    // the names and places of the feed's configured stops
+    Details(usize),
+    // the feed's routes, as routes.rs last fetched them
+    Routes(usize),
+    // the stops one route serves
+    Route(usize, String),
+}
+
+impl Stops {
+    // `upstream` is None for sources without getstops
+    pub fn spawn(
+        upstream: Option<Arc<Upstream>>,
+        feeds: &[FeedConfig],
+        config: Arc<Config>,
+        routes: Arc<Routes>,
+    ) -> Arc<Self> {
+        let stops = Arc::new(Stops {
+            config,
+            known: Mutex::new(Known::default()),
+        });
+
+        if let Some(upstream) = upstream {
+            let background = stops.clone();
+            let feeds = bustime::feeds(feeds);
+            tokio::spawn(async move {
+                routes.wait_for_fetch().await;
+
+                let mut pending = Vec::new();
+                let mut next_scan = Instant::now();
+                let mut retry = Duration::from_secs(STOP_RETRY_SECONDS);
+                loop {
+                    if Instant::now() >= next_scan {
+                        pending = (0..feeds.len())
+                            .flat_map(|feed| [Lookup::Details(feed), Lookup::Routes(feed)])
+                            .collect();
+                        next_scan = Instant::now() + Duration::from_secs(STOP_REFRESH_SECONDS);
+                        retry = Duration::from_secs(STOP_RETRY_SECONDS);
+                    }
+
+                    pending = background.scan(&upstream, &feeds, &routes, pending).await;
+                    if pending.is_empty() {
+                        sleep_until(next_scan).await;
+                    } else {
+                        sleep_until(next_scan.min(Instant::now() + retry)).await;
+                        retry *= 2;
+                    }
+                }
+            });
+        }
+
+        stops
+    }
+
+    // the configured stops `feed` serves
+    fn ids(&self, feed: &FeedConfig) -> Vec<&str> {
+        self.config
+            .stops
+            .iter()
+            .map(|stop| stop.id.as_str())
+            .filter(|id| feed.stops.is_empty() || feed.stops.iter().any(|s| s == id))
+            .collect()
+    }
+
+    // makes each lookup in turn, keeping what it finds as soon as it has it, and returns the
+    // ones that failed
+    async fn scan(
+        &self,
+        upstream: &Upstream,
+        feeds: &[FeedConfig],
+        routes: &Routes,
+        lookups: Vec<Lookup>,
+    ) -> Vec<Lookup> {
+        let mut queue = VecDeque::from(lookups);
+        let mut failed = Vec::new();
+        while let Some(lookup) = queue.pop_front() {
+            match lookup {
+                Lookup::Details(feed) => {
+                    match bustime::fetch_stops(upstream, &feeds[feed], &self.ids(&feeds[feed]))
+                        .await
+                    {
+                        Ok(details) => {
+                            let mut known = self.known.lock().unwrap();
+                            for stop in details {
+                                known.details.insert(stop.id.clone(), stop);
+                            }
+                        }
+                        Err(e) => {
+                            println!("Fetching stops of {} failed: {:?}", feeds[feed].name, e);
+                            failed.push(lookup);
+                        }
+                    }
+                }
+                Lookup::Routes(feed) => {
+                    // routes.rs says why when it has none
+                    let listed = routes.of_feed(&feeds[feed]);
+                    if listed.is_empty() {
+                        failed.push(lookup);
+                        continue;
+                    }
+                    // routes the feed stopped listing no longer serve anything
+                    let mut known = self.known.lock().unwrap();
+                    let served = known.served.entry(feeds[feed].name.clone()).or_default();
+                    served.retain(|route, _| listed.contains(route));
+                    queue.extend(listed.into_iter().map(|route| Lookup::Route(feed, route)));
+                }
+                Lookup::Route(feed, route) => {
+                    match bustime::fetch_route_stops(upstream, &feeds[feed], &route).await {
+                        Ok(stops) => {
+                            let ids = self.ids(&feeds[feed]);
+                            let serves = stops
+                                .into_iter()
+                                .filter(|(stop, _)| ids.contains(&stop.id.as_str()))
+                                .map(|(stop, direction)| (stop.id, direction))
+                                .collect();
+                            let mut known = self.known.lock().unwrap();
+                            let served = known.served.entry(feeds[feed].name.clone()).or_default();
+                            served.insert(route, serves);
+                        }
+                        Err(e) => {
+                            println!("Fetching stops of route {} failed: {:?}", route, e);
+                            failed.push(Lookup::Route(feed, route));
+                        }
+                    }
+                }
+            }
+        }
+
+        self.known.lock().unwrap().complete = !failed
+            .iter()
+            .any(|lookup| !matches!(lookup, Lookup::Details(_)));
+        failed
+    }
+
+    // for each of `feeds`, the routes the lookup found serving its configured stops, plus any
+    // predicted at them in `data`, which covers the routes it hasn't got to yet; sorted
+    pub fn routes(&self, feeds: &[FeedConfig], data: &FrontendResponse) -> Vec<Vec<String>> {
+        let predicted = bustime::routes_by_feed(feeds, data);
+        let known = self.known.lock().unwrap();
+        feeds
+            .iter()
+            .zip(predicted)
+            .map(|(feed, predicted)| {
+                let mut routes: BTreeSet<String> = predicted.into_iter().collect();
+                if let Some(served) = known.served.get(&feed.name) {
+                    routes.extend(
+                        served
+                            .iter()
+                            .filter(|(_, stops)| !stops.is_empty())
+                            .map(|(route, _)| route.clone()),
+                    );
+                }
+                routes.into_iter().collect()
+            })
+            .collect()
+    }
+
+    // the configured stops with whatever PRT has said about them
+    fn info<'a>(&self, stops: impl Iterator<Item = &'a StopConfig>) -> Vec<StopInfo> {
+        let known = self.known.lock().unwrap();
+        stops
+            .map(|stop| {
+                let details = known.details.get(&stop.id);
+                let directions: BTreeSet<&str> = known
+                    .served
+                    .values()
+                    .flat_map(BTreeMap::values)
+                    .flatten()
+                    .filter(|(id, _)| *id == stop.id)
+                    .map(|(_, direction)| direction.as_str())
+                    .collect();
+                let direction = match (known.complete, directions.first()) {
+                    (true, Some(direction)) if directions.len() == 1 => Some(direction.to_string()),
+                    _ => None,
+                };
+                StopInfo {
+                    config: stop.clone(),
+                    name: details.map(|d| d.name.clone()),
+                    direction,
+                    lat: details.map(|d| d.lat),
+                    lon: details.map(|d| d.lon),
+                }
+            })
+            .collect()
+    }
+}
+
+// configured stops and their display metadata, in sign order
+pub async fn get_stops(State(state): State<AppState>) -> Json<Vec<StopInfo>> {
+    Json(state.stops.info(state.config.stops.iter()))
+}
+
+// stop metadata for one sign, in the order the sign lists its stops
+pub async fn get_sign_stops(
+    State(state): State<AppState>,
+    Path(sign_id): Path<String>,
+) -> Result<Json<Vec<StopInfo>>, AppError> {
+    let sign = state
+        .config
+        .sign(&sign_id)
+        .ok_or(AppError::UnknownSign(sign_id))?;
+
+    let stops = sign.stops.iter().filter_map(|id| state.config.stop(id));
+    Ok(Json(state.stops.info(stops)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::{self, Mode, testing::arrival};
+    use crate::upstream::ApiKey;
+
+    fn stops() -> Stops {
+        Stops {
+            config: Arc::new(Config::default()),
+            known: Mutex::new(Known::default()),
+        }
+    }
+
+    // route -> (stop, direction) pairs, as one feed's scan would leave them
+    fn served(routes: &[(&str, &[(&str, &str)])]) -> BTreeMap<String, Vec<(String, String)>> {
+        routes
+            .iter()
+            .map(|(route, stops)| {
+                let stops = stops
+                    .iter()
+                    .map(|(stop, direction)| (stop.to_string(), direction.to_string()))
+                    .collect();
+                (route.to_string(), stops)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn merges_prt_details_with_local_metadata() {
+        let stops = stops();
+        let raw = r#"{"bustime-response": {"stops": [{"stpid": "7117",
+            "stpnm": "Forbes Ave at Morewood Ave (Carnegie Mellon)", "lat": "40.4445", "lon": -79.9427}]}}"#;
+        {
+            let mut known = stops.known.lock().unwrap();
+            for stop in bustime::parse_stops(raw, &ApiKey::new(String::new())).unwrap() {
+                known.details.insert(stop.id.clone(), stop);
+            }
+            known.served = HashMap::from([(
+                "Port Authority Bus".to_string(),
+                served(&[
+                    ("61C", &[("7117", "OUTBOUND"), ("4407", "INBOUND")]),
+                    ("67", &[("7117", "OUTBOUND"), ("4407", "OUTBOUND")]),
+                ]),
+            )]);
+        }
+
+        let info = stops.info(stops.config.stops.iter());
+        assert_eq!(info[0].direction, None, "some routes not looked up yet");
+
+        stops.known.lock().unwrap().complete = true;
+        let info = stops.info(stops.config.stops.iter());
+        assert_eq!(info[0].config.label.as_deref(), Some("UC Side"));
+        assert_eq!(
+            info[0].name.as_deref(),
+            Some("Forbes Ave at Morewood Ave (Carnegie Mellon)")
+        );
+        assert_eq!((info[0].lat, info[0].lon), (Some(40.4445), Some(-79.9427)));
+        assert_eq!(info[0].direction.as_deref(), Some("OUTBOUND"));
+        assert_eq!(info[1].name, None, "PRT didn't know it");
+        assert_eq!(info[1].direction, None, "routes disagree");
+    }
+
+    #[test]
+    fn routes_come_from_the_lookup_and_the_predictions() {
+        let stops = stops();
+        let feed = |name: &str, mode| FeedConfig {
+            name: name.to_string(),
+            mode,
+            stops: Vec::new(),
+        };
+        let feeds = [
+            feed("Port Authority Bus", Mode::Bus),
+            feed("Light Rail", Mode::Rail),
+        ];
+        assert_eq!(
+            stops.routes(&feeds, &FrontendResponse::new()),
+            vec![Vec::<String>::new(); 2]
+        );
+
+        // found so far: the 61C serves a stop, the 28X doesn't
+        stops.known.lock().unwrap().served = HashMap::from([(
+            "Port Authority Bus".to_string(),
+            served(&[("61C", &[("7117", "OUTBOUND")]), ("28X", &[])]),
+        )]);
+        assert_eq!(
+            stops.routes(&feeds, &FrontendResponse::new()),
+            vec![vec!["61C".to_string()], Vec::new()]
+        );
+
+        // a predicted route the lookup hasn't got to yet
+        let data = source::group(vec![
+            arrival("4407", "71B", "Highland Park", 4),
+            arrival("7117", "61C", "McKeesport", 6),
+        ]);
+        assert_eq!(
+            stops.routes(&feeds, &data),
+            vec![vec!["61C".to_string(), "71B".to_string()], Vec::new()]
+        );
+    }
+}
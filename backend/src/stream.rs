@@ -0,0 +1,213 @@
+// server-sent events
+// pushes a fresh payload every time the poller publishes, instead of the sign polling
+// pass ?tick=true to also get the countdowns re-sent once a second between polls
+// outside service hours, with no buses left to show, a "no_service" event says when
+// service resumes instead
+
+use crate::config::SignProfile;
+use crate::{AppError, AppState, current_predictions, signs};
+use axum::{
+    extract::{Path, Query, State},
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures_util::{Stream, stream};
+use serde::Deserialize;
+use std::{convert::Infallible, future, time::Duration};
+use tokio::time::{Interval, MissedTickBehavior, interval};
+
+#[derive(Deserialize)]
+pub struct StreamParams {
+    #[serde(default)]
+    tick: bool,
+}
+
+pub async fn get_predictions_stream(
+    State(state): State<AppState>,
+    Query(params): Query<StreamParams>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    predictions_stream(state, None, params.tick)
+}
+
+pub async fn get_sign_predictions_stream(
+    State(state): State<AppState>,
+    Path(sign_id): Path<String>,
+    Query(params): Query<StreamParams>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError> {
+    let sign = state
+        .config
+        .sign(&sign_id)
+        .cloned()
+        .ok_or(AppError::UnknownSign(sign_id))?;
+
+    Ok(predictions_stream(state, Some(sign), params.tick))
+}
+
+fn predictions_stream(
+    state: AppState,
+    sign: Option<SignProfile>,
+    tick: bool,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let viewer = state.viewers.open_stream();
+    let mut updates = state.poller.subscribe();
+    // send the current snapshot as soon as the client connects
+    updates.mark_changed();
+
+    let ticker = tick.then(|| {
+        let mut ticker = interval(Duration::from_secs(1));
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        ticker
+    });
+
+    // the viewer guard lives in the stream state, so dropping the stream releases it
+    let events = stream::unfold(
+        (state, sign, updates, ticker, viewer),
+        |(state, sign, mut updates, mut ticker, viewer)| async move {
+            tokio::select! {
+                changed = updates.changed() => {
+                    // the poller is gone, so nothing will ever be sent again
+                    if changed.is_err() {
+                        return None;
+                    }
+                }
+                _ = next_tick(&mut ticker) => {}
+            }
+
+            let event = predictions_event(&state, sign.as_ref()).await;
+            Some((Ok(event), (state, sign, updates, ticker, viewer)))
+        },
+    );
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+async fn next_tick(ticker: &mut Option<Interval>) {
+    match ticker {
+        Some(ticker) => {
+            ticker.tick().await;
+        }
+        None => future::pending().await,
+    }
+}
+
+// predictions go out as plain messages, failures as "upstream_error" events
+async fn predictions_event(state: &AppState, sign: Option<&SignProfile>) -> Event {
+    match current_predictions(state).await {
+        Ok(predictions) => {
+            let data = match sign {
+                Some(sign) => signs::view(sign, &predictions.data),
+                None => predictions.data,
+            };
+            if !predictions.service.in_service && data.values().all(Vec::is_empty) {
+                return Event::default()
+                    .event("no_service")
+                    .json_data(predictions.service)
+                    .expect("service status always serializes");
+            }
+            Event::default()
+                .json_data(data)
+                .expect("predictions always serialize")
+        }
+        Err(e) => {
+            let (_, message) = e.status_and_message();
+            Event::default()
+                .event("upstream_error")
+                .json_data(serde_json::json!({ "error": message }))
+                .expect("error messages always serialize")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::testing::{InMemorySource, arrival};
+    use crate::testing;
+    use crate::upstream::UpstreamFailure;
+    use axum::response::IntoResponse;
+    use futures_util::StreamExt;
+    use serde_json::Value;
+    use std::sync::Arc;
+
+    // the event a client gets as soon as it connects, as its name (None for plain messages)
+    // and data
+    async fn first_event(
+        sse: Sse<impl Stream<Item = Result<Event, Infallible>> + Send + 'static>,
+    ) -> (Option<String>, Value) {
+        let mut body = sse.into_response().into_body().into_data_stream();
+        let chunk = body.next().await.unwrap().unwrap();
+        let text = String::from_utf8(chunk.to_vec()).unwrap();
+
+        let mut name = None;
+        let mut data = String::new();
+        for line in text.lines() {
+            if let Some(event) = line.strip_prefix("event: ") {
+                name = Some(event.to_string());
+            } else if let Some(line) = line.strip_prefix("data: ") {
+                data.push_str(line);
+            }
+        }
+        (name, serde_json::from_str(&data).unwrap())
+    }
+
+    fn no_tick() -> Query<StreamParams> {
+        Query(StreamParams { tick: false })
+    }
+
+    #[tokio::test]
+    async fn sends_route_groups_by_stop() {
+        let source = Arc::new(InMemorySource::new(vec![
+            arrival("7117", "61C", "McKeesport", 5),
+            arrival("4407", "67", "Monroeville", 9),
+        ]));
+        let state = testing::state(source).await;
+
+        let (name, data) = first_event(get_predictions_stream(State(state), no_tick()).await).await;
+        assert_eq!(name, None, "predictions are plain messages");
+        let groups = data["7117"].as_array().unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0]["route"], "61C");
+        assert_eq!(groups[0]["destination"], "McKeesport");
+        assert_eq!(groups[0]["mode"], "bus");
+        assert!(groups[0]["arrivals"][0]["seconds"].is_i64());
+        assert_eq!(data["4407"][0]["route"], "67");
+    }
+
+    #[tokio::test]
+    async fn sign_streams_follow_the_profile() {
+        let source = Arc::new(InMemorySource::new(vec![arrival(
+            "7117",
+            "61C",
+            "McKeesport",
+            5,
+        )]));
+        let state = testing::state(source).await;
+
+        let sse =
+            get_sign_predictions_stream(State(state.clone()), Path("default".into()), no_tick())
+                .await
+                .unwrap();
+        let (name, data) = first_event(sse).await;
+        assert_eq!(name, None);
+        assert_eq!(data["7117"][0]["route"], "61C");
+        assert_eq!(
+            data["4407"],
+            Value::Array(Vec::new()),
+            "every stop of the sign"
+        );
+
+        let unknown =
+            get_sign_predictions_stream(State(state), Path("lobby".into()), no_tick()).await;
+        assert!(matches!(unknown, Err(AppError::UnknownSign(_))));
+    }
+
+    #[tokio::test]
+    async fn sends_upstream_errors_as_their_own_event() {
+        let source = Arc::new(InMemorySource::new(Vec::new()));
+        *source.result.lock().unwrap() = Err(AppError::UpstreamError(UpstreamFailure::Timeout));
+        let state = testing::state(source).await;
+
+        let (name, data) = first_event(get_predictions_stream(State(state), no_tick()).await).await;
+        assert_eq!(name.as_deref(), Some("upstream_error"));
+        assert!(data["error"].is_string());
+    }
+}
@@ -0,0 +1,251 @@
+// requests to PRT, and how they fail
+// every BusTime call goes through Upstream, which adds the key, enforces timeouts, retries
+// transient failures with jittered backoff, stops calling PRT while the circuit is open and
+// counts every request against the key's daily budget
+//
+// BusTime takes the key as a query parameter, so anything that echoes the request URL
+// (reqwest errors do) would leak it; nothing here ever formats a URL or the raw key
+
+use crate::BASE_URL;
+use crate::budget::Budget;
+use crate::circuit::{CircuitBreaker, CircuitStatus};
+use std::{
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+// first retry waits around this long, doubling on each further retry
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(250);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(2);
+
+pub struct UpstreamSettings {
+    pub connect_timeout: Duration,
+    // the whole request, including reading the body
+    pub timeout: Duration,
+    // extra attempts after a transient failure
+    pub retries: u32,
+    // consecutive failed requests before the circuit opens
+    pub failure_threshold: u32,
+    pub cooldown: Duration,
+}
+
+pub struct Upstream {
+    client: reqwest::Client,
+    api_key: ApiKey,
+    retries: u32,
+    breaker: CircuitBreaker,
+    budget: Budget,
+    pub stats: UpstreamStats,
+}
+
+// counters for /metrics
+#[derive(Default)]
+pub struct UpstreamStats {
+    pub requests: AtomicU64,
+    pub retries: AtomicU64,
+    pub failures: AtomicU64,
+    // requests never sent because the circuit was open
+    pub short_circuited: AtomicU64,
+}
+
+impl Upstream {
+    pub fn new(api_key: ApiKey, settings: &UpstreamSettings, budget: Budget) -> Self {
+        let client = reqwest::Client::builder()
+            .connect_timeout(settings.connect_timeout)
+            .timeout(settings.timeout)
+            .build()
+            .expect("failed to build HTTP client");
+
+        Upstream {
+            client,
+            api_key,
+            retries: settings.retries,
+            breaker: CircuitBreaker::new(settings.failure_threshold, settings.cooldown),
+            budget,
+            stats: UpstreamStats::default(),
+        }
+    }
+
+    pub fn api_key(&self) -> &ApiKey {
+        &self.api_key
+    }
+
+    // for other downloads that should give up as soon as PRT requests do (see gtfs.rs)
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    pub fn circuit(&self) -> CircuitStatus {
+        self.breaker.status()
+    }
+
+    pub fn budget(&self) -> &Budget {
+        &self.budget
+    }
+
+    // GET {BASE_URL}/{endpoint}, with the key and format=json added to `params`
+    pub async fn get_text(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+    ) -> Result<String, UpstreamFailure> {
+        if !self.breaker.allow_request() {
+            self.stats.short_circuited.fetch_add(1, Ordering::Relaxed);
+            return Err(UpstreamFailure::CircuitOpen);
+        }
+
+        let mut attempt = 0;
+        loop {
+            // checked per attempt, retries cost as much as first tries
+            if !self.budget.try_consume() {
+                self.breaker.cancel_trial();
+                return Err(UpstreamFailure::BudgetExhausted);
+            }
+            self.stats.requests.fetch_add(1, Ordering::Relaxed);
+
+            match self.send(endpoint, params).await {
+                Ok(text) => {
+                    self.breaker.record_success();
+                    return Ok(text);
+                }
+                Err(failure) if failure.is_transient() && attempt < self.retries => {
+                    self.stats.retries.fetch_add(1, Ordering::Relaxed);
+                    tokio::time::sleep(retry_delay(attempt)).await;
+                    attempt += 1;
+                }
+                Err(failure) => {
+                    self.stats.failures.fetch_add(1, Ordering::Relaxed);
+                    self.breaker.record_failure();
+                    return Err(failure);
+                }
+            }
+        }
+    }
+
+    async fn send(
+        &self,
+        endpoint: &str,
+        params: &[(&str, &str)],
+    ) -> Result<String, UpstreamFailure> {
+        let url = format!("{}/{}", BASE_URL, endpoint);
+
+        let resp = self
+            .client
+            .get(&url)
+            .query(&[("key", self.api_key.expose()), ("format", "json")])
+            .query(params)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| UpstreamFailure::from_reqwest(e, &self.api_key))?;
+
+        resp.text()
+            .await
+            .map_err(|e| UpstreamFailure::from_reqwest(e, &self.api_key))
+    }
+}
+
+// exponential backoff with equal jitter, so signs restarted together don't retry in lockstep
+fn retry_delay(attempt: u32) -> Duration {
+    let ceiling = RETRY_BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(RETRY_MAX_DELAY);
+    let half = ceiling.as_millis() as u64 / 2;
+    Duration::from_millis(half + fastrand::u64(0..=half))
+}
+
+// the PRT API key; only `expose` hands out the real value, for building the request
+#[derive(Clone)]
+pub struct ApiKey(String);
+
+impl ApiKey {
+    pub fn new(key: String) -> Self {
+        ApiKey(key)
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    // strips the key out of text that came back from upstream (PRT sometimes echoes parameters)
+    pub fn redact(&self, text: &str) -> String {
+        if self.0.is_empty() {
+            return text.to_string();
+        }
+        text.replace(&self.0, "[redacted]")
+    }
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ApiKey([redacted])")
+    }
+}
+
+// why a request to PRT failed, described in our own words rather than reqwest's
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UpstreamFailure {
+    Timeout,
+    Connect,
+    // PRT answered with a non-success HTTP status
+    Status(u16),
+    Body,
+    Request,
+    // not attempted, the circuit breaker is open
+    CircuitOpen,
+    // not attempted, today's request budget is used up
+    BudgetExhausted,
+}
+
+impl UpstreamFailure {
+    // worth retrying straight away; 4xx answers (including rate limiting) are not
+    fn is_transient(self) -> bool {
+        match self {
+            UpstreamFailure::Timeout | UpstreamFailure::Connect | UpstreamFailure::Body => true,
+            UpstreamFailure::Status(status) => status >= 500,
+            UpstreamFailure::Request
+            | UpstreamFailure::CircuitOpen
+            | UpstreamFailure::BudgetExhausted => false,
+        }
+    }
+
+    // logs the full error (minus its URL) and keeps only the category
+    pub fn from_reqwest(e: reqwest::Error, key: &ApiKey) -> Self {
+        let failure = if e.is_timeout() {
+            UpstreamFailure::Timeout
+        } else if e.is_connect() {
+            UpstreamFailure::Connect
+        } else if let Some(status) = e.status() {
+            UpstreamFailure::Status(status.as_u16())
+        } else if e.is_body() || e.is_decode() {
+            UpstreamFailure::Body
+        } else {
+            UpstreamFailure::Request
+        };
+
+        println!(
+            "Upstream request failed: {}",
+            key.redact(&e.without_url().to_string())
+        );
+        failure
+    }
+}
+
+impl fmt::Display for UpstreamFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UpstreamFailure::Timeout => f.write_str("request to PRT timed out"),
+            UpstreamFailure::Connect => f.write_str("could not connect to PRT"),
+            UpstreamFailure::Status(status) => write!(f, "PRT returned HTTP {}", status),
+            UpstreamFailure::Body => f.write_str("could not read PRT response"),
+            UpstreamFailure::Request => f.write_str("request to PRT failed"),
+            UpstreamFailure::CircuitOpen => {
+                f.write_str("PRT requests paused after repeated failures")
+            }
+            UpstreamFailure::BudgetExhausted => {
+                f.write_str("daily PRT request budget used up until midnight")
+            }
+        }
+    }
+}
@@ -0,0 +1,281 @@
+// versioned response envelope
+// v1 is a bare map of stop ID -> route groups, which can't say how old the data is or
+// tell "PRT failed" apart from "no buses"; v2 wraps the same route groups with that context
+// and always answers 200 for upstream trouble, listing it under `errors` instead
+
+use crate::circuit::CircuitStatus;
+use crate::config::{SignProfile, StopConfig};
+use crate::schedule::RouteServiceDay;
+use crate::service::ServiceStatus;
+use crate::{AppError, AppState, PrtMessage, RouteGroup, StopFailure, current_predictions, signs};
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+const VERSION: u32 = 2;
+
+#[derive(Serialize)]
+pub struct Envelope {
+    version: u32,
+    generated_at: DateTime<Utc>,
+    // None if there has never been a successful fetch to serve
+    fetched_at: Option<DateTime<Utc>>,
+    age_seconds: Option<i64>,
+    // the latest poll failed and this is the last good data
+    stale: bool,
+    // where the route groups came from, see source.rs
+    source: &'static str,
+    // circuit breaker state for requests to PRT
+    upstream: CircuitStatus,
+    // whether buses are running, see service.rs
+    service: ServiceStatus,
+    stops: Vec<StopPredictions>,
+    errors: Vec<ErrorEntry>,
+}
+
+#[derive(Serialize)]
+struct StopPredictions {
+    #[serde(flatten)]
+    stop: StopConfig,
+    routes: Vec<RouteGroup>,
+    // each route's first and last trip today, if a timetable is configured (see schedule.rs)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    service_day: Vec<RouteServiceDay>,
+}
+
+#[derive(Serialize)]
+struct ErrorEntry {
+    code: &'static str,
+    message: String,
+    // set when PRT tied the error to one stop or route
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    route: Option<String>,
+}
+
+impl From<&AppError> for ErrorEntry {
+    fn from(e: &AppError) -> Self {
+        ErrorEntry {
+            code: e.code(),
+            message: e.status_and_message().1,
+            stop: None,
+            route: None,
+        }
+    }
+}
+
+impl From<&PrtMessage> for ErrorEntry {
+    fn from(m: &PrtMessage) -> Self {
+        ErrorEntry {
+            code: m.kind.code(),
+            message: m.message.clone(),
+            stop: m.stop.clone(),
+            route: m.route.clone(),
+        }
+    }
+}
+
+impl From<&StopFailure> for ErrorEntry {
+    fn from(f: &StopFailure) -> Self {
+        ErrorEntry {
+            stop: Some(f.stop.clone()),
+            ..ErrorEntry::from(&f.error)
+        }
+    }
+}
+
+// every configured stop, in config order
+pub async fn get_predictions(State(state): State<AppState>) -> Json<Envelope> {
+    Json(envelope(&state, None).await)
+}
+
+// one sign's stops, filtered and ordered by its profile
+pub async fn get_sign_predictions(
+    State(state): State<AppState>,
+    Path(sign_id): Path<String>,
+) -> Result<Json<Envelope>, AppError> {
+    let sign = state
+        .config
+        .sign(&sign_id)
+        .ok_or(AppError::UnknownSign(sign_id))?;
+
+    Ok(Json(envelope(&state, Some(sign)).await))
+}
+
+async fn envelope(state: &AppState, sign: Option<&SignProfile>) -> Envelope {
+    let generated_at = Utc::now();
+
+    let stops: Vec<&StopConfig> = match sign {
+        Some(sign) => sign
+            .stops
+            .iter()
+            .filter_map(|id| state.config.stop(id))
+            .collect(),
+        None => state.config.stops.iter().collect(),
+    };
+
+    let predictions = match current_predictions(state).await {
+        Ok(predictions) => predictions,
+        // nothing recent enough to serve, but the stops are still described
+        Err(e) => {
+            return Envelope {
+                version: VERSION,
+                generated_at,
+                fetched_at: None,
+                age_seconds: None,
+                stale: false,
+                source: state.poller.source_name(),
+                upstream: state.upstream.circuit(),
+                service: state.config.service_status(generated_at),
+                stops: stops
+                    .into_iter()
+                    .map(|stop| StopPredictions {
+                        stop: stop.clone(),
+                        routes: Vec::new(),
+                        service_day: service_day(state, sign, stop, generated_at),
+                    })
+                    .collect(),
+                errors: vec![ErrorEntry::from(&e)],
+            };
+        }
+    };
+
+    let mut data = match sign {
+        Some(sign) => signs::view(sign, &predictions.data),
+        None => predictions.data,
+    };
+
+    let mut errors: Vec<ErrorEntry> = predictions
+        .poll_error
+        .iter()
+        .map(ErrorEntry::from)
+        .collect();
+    errors.extend(predictions.failures.iter().map(ErrorEntry::from));
+    errors.extend(predictions.messages.iter().map(ErrorEntry::from));
+
+    Envelope {
+        version: VERSION,
+        generated_at,
+        fetched_at: predictions.fetched_at,
+        age_seconds: predictions.age_seconds,
+        stale: predictions.stale,
+        source: state.poller.source_name(),
+        upstream: state.upstream.circuit(),
+        service: predictions.service,
+        stops: stops
+            .into_iter()
+            .map(|stop| StopPredictions {
+                stop: stop.clone(),
+                routes: data.remove(&stop.id).unwrap_or_default(),
+                service_day: service_day(state, sign, stop, generated_at),
+            })
+            .collect(),
+        errors,
+    }
+}
+
+// only the routes the sign shows
+fn service_day(
+    state: &AppState,
+    sign: Option<&SignProfile>,
+    stop: &StopConfig,
+    now: DateTime<Utc>,
+) -> Vec<RouteServiceDay> {
+    let Some(schedule) = &state.schedule else {
+        return Vec::new();
+    };
+    let mut routes = schedule.service_day(&stop.id, now);
+    routes.retain(|day| sign.is_none_or(|sign| sign.shows_route(&day.route)));
+    routes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::testing::{InMemorySource, arrival};
+    use crate::testing;
+    use crate::upstream::UpstreamFailure;
+    use serde_json::{Value, json};
+    use std::sync::Arc;
+
+    fn shape(envelope: &Envelope) -> Value {
+        serde_json::to_value(envelope).unwrap()
+    }
+
+    #[tokio::test]
+    async fn wraps_every_stop_with_its_context() {
+        let source = Arc::new(InMemorySource::new(vec![
+            arrival("4407", "61C", "McKeesport", 5),
+            arrival("4407", "67", "Monroeville", 9),
+        ]));
+        let Json(envelope) = get_predictions(State(testing::state(source).await)).await;
+
+        let body = shape(&envelope);
+        assert_eq!(body["version"], 2);
+        assert_eq!(body["stale"], false);
+        assert_eq!(body["source"], "memory");
+        assert!(body["fetched_at"].is_string());
+        assert!(body["age_seconds"].is_i64());
+        assert_eq!(body["upstream"]["state"], "closed");
+        assert_eq!(body["service"]["in_service"], true);
+        assert_eq!(body["errors"], json!([]));
+
+        // every configured stop in config order, with its metadata flattened in
+        let stops = body["stops"].as_array().unwrap();
+        assert_eq!(stops.len(), 2);
+        assert_eq!(stops[0]["id"], "7117");
+        assert_eq!(stops[0]["label"], "UC Side");
+        assert_eq!(stops[0]["routes"], json!([]));
+        assert!(stops[0].get("service_day").is_none(), "no timetable");
+        let routes = stops[1]["routes"].as_array().unwrap();
+        assert_eq!(routes[0]["route"], "61C");
+        assert_eq!(routes[0]["destination"], "McKeesport");
+        assert!(routes[0]["arrivals"][0]["seconds"].is_i64());
+    }
+
+    #[tokio::test]
+    async fn sign_envelopes_follow_the_profile() {
+        let source = Arc::new(InMemorySource::new(vec![arrival(
+            "7117",
+            "61C",
+            "McKeesport",
+            5,
+        )]));
+        let state = testing::state(source).await;
+
+        let Json(envelope) = get_sign_predictions(State(state.clone()), Path("default".into()))
+            .await
+            .unwrap();
+        let body = shape(&envelope);
+        assert_eq!(body["version"], 2);
+        assert_eq!(body["stops"][0]["routes"][0]["route"], "61C");
+
+        let unknown = get_sign_predictions(State(state), Path("lobby".into())).await;
+        assert!(matches!(unknown, Err(AppError::UnknownSign(_))));
+    }
+
+    #[tokio::test]
+    async fn lists_upstream_failures_as_errors() {
+        let source = Arc::new(InMemorySource::new(Vec::new()));
+        *source.result.lock().unwrap() = Err(AppError::UpstreamError(UpstreamFailure::Timeout));
+        let Json(envelope) = get_predictions(State(testing::state(source).await)).await;
+
+        let body = shape(&envelope);
+        assert_eq!(body["fetched_at"], Value::Null);
+        assert_eq!(body["age_seconds"], Value::Null);
+        assert_eq!(body["stops"][0]["id"], "7117", "stops are still described");
+        assert_eq!(body["stops"][0]["routes"], json!([]));
+        let errors = body["errors"].as_array().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0]["code"],
+            AppError::UpstreamError(UpstreamFailure::Timeout).code()
+        );
+        assert!(errors[0]["message"].is_string());
+        assert!(errors[0].get("stop").is_none());
+    }
+}
@@ -0,0 +1,144 @@
+// /vehicles: where the buses serving the configured stops are, from BusTime's getvehicles
+// the routes asked about are the ones serving the configured stops, each from the feed that
+// serves them, as far as the stops lookup has found them (see stops.rs), plus any in the
+// latest predictions, so a lookup that hasn't finished doesn't leave the map empty
+//
+// nothing polls vehicles in the background; they are fetched when asked for and cached for
+// as long as a prediction snapshot would be, one fetch at a time, so any number of map
+// clients cost PRT no more than one call per ten routes per interval, out of the same budget
+
+use crate::config::FeedConfig;
+use crate::source::Mode;
+use crate::upstream::Upstream;
+use crate::{AppError, AppState, CACHE_DURATION_SECONDS, bustime};
+use axum::{Json, extract::State};
+use chrono::{DateTime, FixedOffset, Utc};
+use futures_util::future::join_all;
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+// --- OUTGOING DATA ---
+#[derive(Serialize, Debug, Clone)]
+pub struct Vehicle {
+    pub bus_id: String,
+    pub route: String,
+    pub destination: String,
+    pub lat: f64,
+    pub lon: f64,
+    // degrees clockwise from north
+    pub heading: u16,
+    // mph
+    pub speed: u32,
+    // BusTime's pattern (the route's exact path) the vehicle is on
+    pub pattern_id: u64,
+    // BusTime considers it behind schedule
+    pub delayed: bool,
+    // when the vehicle last reported its position
+    pub updated_at: DateTime<FixedOffset>,
+    pub mode: Mode,
+}
+
+#[derive(Serialize)]
+pub struct VehiclesResponse {
+    // None if there was nothing to ask about
+    fetched_at: Option<DateTime<Utc>>,
+    age_seconds: Option<i64>,
+    vehicles: Vec<Vehicle>,
+}
+
+pub struct Vehicles {
+    upstream: Arc<Upstream>,
+    feeds: Vec<FeedConfig>,
+    // last fetch, held locked while a new one is in flight
+    cache: Mutex<Option<Cached>>,
+}
+
+struct Cached {
+    fetched_at: DateTime<Utc>,
+    // what was asked for, a different set of routes needs a new fetch
+    routes: Vec<Vec<String>>,
+    // failures are cached too, so a broken PRT isn't asked on every request
+    result: Result<Vec<Vehicle>, AppError>,
+}
+
+impl Vehicles {
+    pub fn new(upstream: Arc<Upstream>, feeds: &[FeedConfig]) -> Self {
+        Vehicles {
+            upstream,
+            feeds: bustime::feeds(feeds),
+            cache: Mutex::new(None),
+        }
+    }
+
+    // vehicles on `routes` (one list per feed), from the cache if it is recent enough
+    async fn get(&self, routes: Vec<Vec<String>>) -> Result<VehiclesResponse, AppError> {
+        if routes.iter().all(Vec::is_empty) {
+            return Ok(VehiclesResponse {
+                fetched_at: None,
+                age_seconds: None,
+                vehicles: Vec::new(),
+            });
+        }
+
+        let mut cache = self.cache.lock().await;
+        let fresh = cache.as_ref().is_some_and(|c| {
+            c.routes == routes
+                && Utc::now().signed_duration_since(c.fetched_at).num_seconds()
+                    < CACHE_DURATION_SECONDS as i64
+        });
+        if !fresh {
+            let fetched_at = Utc::now();
+            let results = join_all(
+                self.feeds
+                    .iter()
+                    .zip(&routes)
+                    .filter(|(_, routes)| !routes.is_empty())
+                    .map(|(feed, routes)| bustime::fetch_vehicles(&self.upstream, feed, routes)),
+            )
+            .await;
+            let result = results
+                .into_iter()
+                .collect::<Result<Vec<_>, _>>()
+                .map(|vehicles| vehicles.concat());
+            *cache = Some(Cached {
+                fetched_at,
+                routes,
+                result,
+            });
+        }
+
+        let cached = cache.as_ref().expect("filled above");
+        Ok(VehiclesResponse {
+            fetched_at: Some(cached.fetched_at),
+            age_seconds: Some(
+                Utc::now()
+                    .signed_duration_since(cached.fetched_at)
+                    .num_seconds(),
+            ),
+            vehicles: cached.result.clone()?,
+        })
+    }
+}
+
+pub async fn get_vehicles(
+    State(state): State<AppState>,
+) -> Result<Json<VehiclesResponse>, AppError> {
+    let vehicles = state.vehicles.as_ref().ok_or(AppError::NotAvailable(
+        "vehicle positions need the BusTime source",
+    ))?;
+
+    // asking PRT outside service hours would defeat suspending the poller
+    let routes = if state.config.polling_suspended(Utc::now()) {
+        Vec::new()
+    } else {
+        let data = state
+            .poller
+            .latest()
+            .and_then(|poll| poll.snapshot.as_ref().map(|snapshot| snapshot.data.clone()))
+            .unwrap_or_default();
+        state.stops.routes(&vehicles.feeds, &data)
+    };
+
+    vehicles.get(routes).await.map(Json)
+}
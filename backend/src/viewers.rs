@@ -0,0 +1,62 @@
+// who is looking at a sign right now
+// the poller slows down when nobody is, and wakes up as soon as someone comes back
+
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::Notify;
+
+// a polling client counts as gone after this long without a request
+const IDLE_AFTER: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Default)]
+pub struct Viewers {
+    // open SSE and websocket connections
+    streams: AtomicUsize,
+    last_request: Mutex<Option<Instant>>,
+    woken: Notify,
+}
+
+// held for as long as a stream stays open
+pub struct StreamGuard(Arc<Viewers>);
+
+impl Drop for StreamGuard {
+    fn drop(&mut self) {
+        self.0.streams.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Viewers {
+    pub fn is_idle(&self) -> bool {
+        self.streams.load(Ordering::Relaxed) == 0
+            && self
+                .last_request
+                .lock()
+                .unwrap()
+                .is_none_or(|at| at.elapsed() >= IDLE_AFTER)
+    }
+
+    // called on every request for predictions
+    pub fn touch(&self) {
+        let was_idle = self.is_idle();
+        *self.last_request.lock().unwrap() = Some(Instant::now());
+        if was_idle {
+            self.woken.notify_one();
+        }
+    }
+
+    pub fn open_stream(self: &Arc<Self>) -> StreamGuard {
+        self.touch();
+        self.streams.fetch_add(1, Ordering::Relaxed);
+        StreamGuard(self.clone())
+    }
+
+    // resolves when a viewer shows up after an idle stretch
+    pub async fn woken(&self) {
+        self.woken.notified().await;
+    }
+}
@@ -0,0 +1,280 @@
+// websocket endpoint
+// each client subscribes to the stops it cares about and is sent only what changed in their
+// route groups after every poll, instead of the whole response
+//
+// client -> server: {"type": "subscribe", "stops": ["4407"]}
+//                   {"type": "unsubscribe", "stops": ["4407"]}
+// server -> client: {"type": "diff", "stops": {"4407": {"upsert": [RouteGroup], "remove": [{"route", "destination", "mode"}]}}}
+//                   diffs also carry "service": {"in_service", "resumes_at", "message"} whenever
+//                   service starts or stops (see service.rs), and in the first diff
+//                   {"type": "error", "error": "..."}
+
+use crate::service::ServiceStatus;
+use crate::source::Mode;
+use crate::{AppState, RouteGroup, current_predictions};
+use axum::{
+    extract::{
+        State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ClientMessage {
+    Subscribe { stops: Vec<String> },
+    Unsubscribe { stops: Vec<String> },
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ServerMessage {
+    Diff {
+        stops: BTreeMap<String, StopDiff>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        service: Option<ServiceStatus>,
+    },
+    Error {
+        error: String,
+    },
+}
+
+#[derive(Serialize)]
+struct StopDiff {
+    // new or changed groups, replacing any group with the same route, destination and mode
+    upsert: Vec<RouteGroup>,
+    remove: Vec<RouteKey>,
+}
+
+#[derive(Serialize)]
+struct RouteKey {
+    route: String,
+    destination: String,
+    // a bus and a train can share a route name and destination
+    mode: Mode,
+}
+
+// what one client has subscribed to and what it has been sent so far
+#[derive(Default)]
+struct Session {
+    stops: HashSet<String>,
+    sent: HashMap<String, Vec<RouteGroup>>,
+    service: Option<ServiceStatus>,
+}
+
+pub async fn get_ws(State(state): State<AppState>, ws: WebSocketUpgrade) -> Response {
+    ws.on_upgrade(move |socket| client_session(socket, state))
+}
+
+async fn client_session(mut socket: WebSocket, state: AppState) {
+    let _viewer = state.viewers.open_stream();
+    let mut updates = state.poller.subscribe();
+    let mut session = Session::default();
+
+    loop {
+        let reply = tokio::select! {
+            msg = socket.recv() => match msg {
+                Some(Ok(Message::Text(text))) => session.handle(&state, &text).await,
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // pings are answered by axum
+                Some(Ok(_)) => None,
+            },
+            changed = updates.changed() => {
+                if changed.is_err() {
+                    break;
+                }
+                session.diff(&state).await
+            }
+        };
+
+        if let Some(reply) = reply {
+            let text = serde_json::to_string(&reply).expect("server messages always serialize");
+            if socket.send(Message::Text(text)).await.is_err() {
+                break;
+            }
+        }
+    }
+}
+
+impl Session {
+    async fn handle(&mut self, state: &AppState, text: &str) -> Option<ServerMessage> {
+        let request: ClientMessage = match serde_json::from_str(text) {
+            Ok(request) => request,
+            Err(e) => {
+                return Some(ServerMessage::Error {
+                    error: format!("Invalid message: {}", e),
+                });
+            }
+        };
+
+        match request {
+            ClientMessage::Subscribe { stops } => {
+                if let Some(unknown) = stops.iter().find(|id| state.config.stop(id).is_none()) {
+                    return Some(ServerMessage::Error {
+                        error: format!("Unknown stop: {}", unknown),
+                    });
+                }
+                self.stops.extend(stops);
+                // newly subscribed stops have nothing in `sent`, so they arrive in full
+                self.diff(state).await
+            }
+            ClientMessage::Unsubscribe { stops } => {
+                for stop in stops {
+                    self.stops.remove(&stop);
+                    self.sent.remove(&stop);
+                }
+                None
+            }
+        }
+    }
+
+    // None if nothing the client subscribed to has changed
+    async fn diff(&mut self, state: &AppState) -> Option<ServerMessage> {
+        let predictions = match current_predictions(state).await {
+            Ok(predictions) => predictions,
+            Err(e) => {
+                let (_, error) = e.status_and_message();
+                return Some(ServerMessage::Error { error });
+            }
+        };
+
+        let mut stops = BTreeMap::new();
+        for stop in &self.stops {
+            let current = predictions.data.get(stop).cloned().unwrap_or_default();
+            let previous = self.sent.get(stop).map(Vec::as_slice).unwrap_or_default();
+
+            let diff = diff_groups(previous, &current);
+            if !diff.upsert.is_empty() || !diff.remove.is_empty() {
+                stops.insert(stop.clone(), diff);
+            }
+            self.sent.insert(stop.clone(), current);
+        }
+
+        let service = (self.service.as_ref().map(|s| s.in_service)
+            != Some(predictions.service.in_service))
+        .then(|| predictions.service.clone());
+        if service.is_some() {
+            self.service = service.clone();
+        }
+
+        (!stops.is_empty() || service.is_some()).then_some(ServerMessage::Diff { stops, service })
+    }
+}
+
+fn diff_groups(previous: &[RouteGroup], current: &[RouteGroup]) -> StopDiff {
+    let same_key = |a: &RouteGroup, b: &RouteGroup| {
+        a.route == b.route && a.destination == b.destination && a.mode == b.mode
+    };
+
+    let upsert = current
+        .iter()
+        .filter(|group| !previous.contains(group))
+        .cloned()
+        .collect();
+
+    let remove = previous
+        .iter()
+        .filter(|old| !current.iter().any(|group| same_key(old, group)))
+        .map(|old| RouteKey {
+            route: old.route.clone(),
+            destination: old.destination.clone(),
+            mode: old.mode,
+        })
+        .collect();
+
+    StopDiff { upsert, remove }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::{self, testing::arrival};
+
+    fn keys(remove: &[RouteKey]) -> Vec<(&str, &str)> {
+        remove
+            .iter()
+            .map(|key| (key.route.as_str(), key.destination.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn diffs_added_removed_changed_and_unchanged_groups() {
+        let previous = source::group(vec![
+            arrival("7117", "61C", "McKeesport", 5),
+            arrival("7117", "61D", "Waterfront", 8),
+            arrival("7117", "67", "Monroeville", 12),
+        ])
+        .remove("7117")
+        .unwrap();
+
+        let mut current = previous.clone();
+        // 61C unchanged, 61D changed, 67 removed, 58 added
+        current[1].arrivals[0].seconds -= 60;
+        current.retain(|group| group.route != "67");
+        current.extend(
+            source::group(vec![arrival("7117", "58", "Greenfield", 3)])
+                .remove("7117")
+                .unwrap(),
+        );
+
+        let diff = diff_groups(&previous, &current);
+        let upserted: Vec<&str> = diff.upsert.iter().map(|g| g.route.as_str()).collect();
+        assert_eq!(upserted, ["61D", "58"]);
+        assert_eq!(
+            diff.upsert[0].arrivals[0].seconds,
+            current[1].arrivals[0].seconds
+        );
+        assert_eq!(keys(&diff.remove), [("67", "Monroeville")]);
+
+        let unchanged = diff_groups(&current, &current);
+        assert!(unchanged.upsert.is_empty() && unchanged.remove.is_empty());
+
+        // a stop seen for the first time arrives in full
+        let first = diff_groups(&[], &current);
+        assert_eq!(first.upsert.len(), 3);
+        assert!(first.remove.is_empty());
+
+        // and one with nothing left is removed group by group
+        let emptied = diff_groups(&current, &[]);
+        assert!(emptied.upsert.is_empty());
+        assert_eq!(
+            keys(&emptied.remove),
+            [
+                ("61C", "McKeesport"),
+                ("61D", "Waterfront"),
+                ("58", "Greenfield")
+            ]
+        );
+    }
+
+    #[test]
+    fn keys_groups_by_mode_too() {
+        let mut train = arrival("4407", "RED", "South Hills Village", 4);
+        train.mode = Mode::Rail;
+        let previous = source::group(vec![
+            arrival("4407", "RED", "South Hills Village", 6),
+            train,
+        ])
+        .remove("4407")
+        .unwrap();
+        assert_eq!(previous.len(), 2);
+
+        // the replacement bus is gone, the train still runs
+        let current: Vec<RouteGroup> = previous
+            .iter()
+            .filter(|group| group.mode == Mode::Rail)
+            .cloned()
+            .collect();
+        let diff = diff_groups(&previous, &current);
+        assert!(diff.upsert.is_empty());
+        assert_eq!(diff.remove.len(), 1);
+        assert_eq!(diff.remove[0].mode, Mode::Bus);
+        assert_eq!(
+            serde_json::to_value(&diff.remove[0]).unwrap(),
+            serde_json::json!({"route": "RED", "destination": "South Hills Village", "mode": "bus"})
+        );
+    }
+}